@@ -0,0 +1,37 @@
+import cv2
+
+def detect_black_frames(video_path, luma_threshold=16, min_run_length=10):
+    # Open the video file
+    video_capture = cv2.VideoCapture(video_path)
+
+    # Average brightness of every frame
+    frame_lumas = []
+    while True:
+        success, frame = video_capture.read()
+        if not success:
+            break
+        frame_lumas.append(cv2.cvtColor(frame, cv2.COLOR_BGR2GRAY).mean())
+
+    # Release the video capture object
+    video_capture.release()
+
+    return find_black_runs(frame_lumas, luma_threshold, min_run_length)
+
+def find_black_runs(frame_lumas, luma_threshold=16, min_run_length=10):
+    # Collect (start, end) frame ranges of consecutive dark frames, end exclusive
+    black_runs = []
+    run_start = None
+    for frame_number, luma in enumerate(frame_lumas):
+        if luma <= luma_threshold:
+            if run_start is None:
+                run_start = frame_number
+        elif run_start is not None:
+            if frame_number - run_start >= min_run_length:
+                black_runs.append((run_start, frame_number))
+            run_start = None
+
+    # A run can last until the end of the video
+    if run_start is not None and len(frame_lumas) - run_start >= min_run_length:
+        black_runs.append((run_start, len(frame_lumas)))
+
+    return black_runs
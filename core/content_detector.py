@@ -0,0 +1,162 @@
+import time
+
+import cv2
+import numpy as np
+from scenedetect import ContentDetector
+
+from core.frame_difference import drop_alpha
+from core.frame_scores import FrameMetrics
+from core.scene_histograms import calculate_hsv_histogram
+
+class MarkItContentDetector(ContentDetector):
+    def __init__(self, on_frame_processed=None, on_frame_scored=None, frame_callback=None, blur_kernel_size=None,
+                 ignore_regions=None, frame_width=None, custom_metrics=None, cooldown_frames=0, collect_histograms=False,
+                 pixel_stride=1, decoded_frames=None, **kwargs):
+        super().__init__(**kwargs)
+        self.on_frame_processed = on_frame_processed
+        self.on_frame_scored = on_frame_scored
+
+        # Called with every frame, its score and per-channel metrics, so other analysis
+        # can share the decode. Frames are taken from decoded_frames, a queue of the
+        # full-resolution frames filled as they are read, when given.
+        self.frame_callback = frame_callback
+        self.decoded_frames = decoded_frames
+        self.last_frame_score = None
+
+        # Gaussian blur kernels must have an odd size
+        if blur_kernel_size is not None and (blur_kernel_size < 3 or blur_kernel_size % 2 == 0):
+            raise ValueError(f"Blur kernel size must be an odd number of at least 3, got {blur_kernel_size}.")
+        self.blur_kernel_size = blur_kernel_size
+
+        # Score every Nth row and column only, a cheap alternative to downscaling
+        self.pixel_stride = pixel_stride
+
+        # Regions are (x, y, width, height) in pixels of the source video, which
+        # is frame_width wide before any downscaling
+        self.ignore_regions = ignore_regions or []
+        self.frame_width = frame_width
+
+        # Extra metrics are (function, weight) pairs, where the function takes the
+        # previous and current frame and returns a score on the same scale
+        self.custom_metrics = custom_metrics or []
+        self.previous_frame_img = None
+
+        # Frames after each cut that can't trigger another one, e.g. while auto-exposure
+        # settles, independent of the minimum scene length
+        self.cooldown_frames = cooldown_frames
+        self.last_cut_frame = None
+
+        # Running sum and count of the HSV histograms of the frames since each cut,
+        # for per-scene color features
+        self.collect_histograms = collect_histograms
+        self.scene_histogram_sums = {}
+        self.histogram_scene_start = None
+
+        # Total seconds spent in the detector, as opposed to decoding, the part of it
+        # spent preparing frames before scoring, and the part spent in callbacks
+        self.processing_time = 0.0
+        self.preprocessing_time = 0.0
+        self.callback_time = 0.0
+
+    def process_frame(self, frame_num, frame_img):
+        processing_started_at = time.monotonic()
+        frame_img = drop_alpha(frame_img)
+        decoded_frame_img = frame_img
+
+        # Keep the colors of the whole frame, before masking and blurring
+        frame_histogram = calculate_hsv_histogram(frame_img) if self.collect_histograms else None
+
+        # Drop the rows and columns that aren't sampled
+        if self.pixel_stride > 1:
+            frame_img = np.ascontiguousarray(frame_img[::self.pixel_stride, ::self.pixel_stride])
+
+        # Black out ignored regions, like logos and tickers, so they don't add to the score
+        if self.ignore_regions:
+            frame_img = self.mask_ignore_regions(frame_img)
+
+        # Blur the frame first so sensor noise doesn't dominate the score
+        if self.blur_kernel_size:
+            frame_img = cv2.GaussianBlur(frame_img, (self.blur_kernel_size, self.blur_kernel_size), 0)
+
+        scoring_started_at = time.monotonic()
+        self.preprocessing_time += scoring_started_at - processing_started_at
+
+        cuts = super().process_frame(frame_num, frame_img)
+        if cuts:
+            self.last_cut_frame = cuts[-1]
+
+        # Add the frame to the scene it belongs to, now that a cut on it is known
+        if frame_histogram is not None:
+            if cuts or self.histogram_scene_start is None:
+                self.histogram_scene_start = cuts[-1] if cuts else frame_num
+            histogram_sum, frame_count = self.scene_histogram_sums.get(self.histogram_scene_start, (0, 0))
+            self.scene_histogram_sums[self.histogram_scene_start] = (histogram_sum + frame_histogram, frame_count + 1)
+        callbacks_started_at = time.monotonic()
+
+        # Share a read-only view of the frame, at full resolution if it was kept
+        if self.frame_callback:
+            if self.decoded_frames:
+                decoded_frame_img = drop_alpha(self.decoded_frames.popleft())
+            frame_view = decoded_frame_img.view()
+            frame_view.flags.writeable = False
+            self.frame_callback(frame_num, frame_view, self.last_frame_score, self.get_frame_metrics(frame_num))
+
+        # Notify the caller, e.g. to report progress
+        if self.on_frame_processed:
+            self.on_frame_processed()
+
+        processing_ended_at = time.monotonic()
+        self.callback_time += processing_ended_at - callbacks_started_at
+        self.processing_time += processing_ended_at - processing_started_at
+
+        return cuts
+
+    def _calculate_frame_score(self, frame_num, frame_img):
+        frame_score = self.calculate_blended_score(frame_num, frame_img)
+
+        # Scores during the cooldown still update the detector's state, but can't cut
+        if self.last_cut_frame is not None and frame_num - self.last_cut_frame <= self.cooldown_frames:
+            frame_score = 0.0
+
+        self.last_frame_score = frame_score
+
+        # Pass every score on, e.g. to a DetectionObserver
+        if self.on_frame_scored:
+            callback_started_at = time.monotonic()
+            self.on_frame_scored(frame_num, frame_score)
+            self.callback_time += time.monotonic() - callback_started_at
+
+        return frame_score
+
+    def calculate_blended_score(self, frame_num, frame_img):
+        frame_score = super()._calculate_frame_score(frame_num, frame_img)
+        if not self.custom_metrics:
+            return frame_score
+
+        # Blend the custom metrics into the content score, which has a weight of 1
+        previous_frame_img = self.previous_frame_img
+        self.previous_frame_img = frame_img
+        if previous_frame_img is None:
+            return frame_score
+
+        weighted_sum = frame_score + sum(weight * metric(previous_frame_img, frame_img) for metric, weight in self.custom_metrics)
+        total_weight = 1 + sum(abs(weight) for _, weight in self.custom_metrics)
+        return weighted_sum / total_weight
+
+    def get_frame_metrics(self, frame_num):
+        # Per-channel deltas are only kept by a stats manager
+        if self.stats_manager is None:
+            return None
+
+        values = self.stats_manager.get_metrics(frame_num, FrameMetrics._fields[1:])
+        return FrameMetrics(frame_num, *(value or 0.0 for value in values))
+
+    def mask_ignore_regions(self, frame_img):
+        # Scale the regions to the (possibly downscaled) frame size
+        scale = frame_img.shape[1] / self.frame_width if self.frame_width else 1.0
+
+        masked_img = frame_img.copy()
+        for x, y, width, height in self.ignore_regions:
+            masked_img[int(y * scale):int((y + height) * scale), int(x * scale):int((x + width) * scale)] = 0
+
+        return masked_img
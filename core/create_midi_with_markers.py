@@ -1,7 +1,7 @@
 import mido
 from mido import MetaMessage, MidiFile, MidiTrack
 
-def create_midi_with_markers(frame_numbers, output_filename, fps=25, bpm=60):
+def create_midi_with_markers(frame_numbers, output_filename, fps=25, bpm=60, marker_texts=None):
     # Video properties
     ticks_per_beat = 960  # Pro Tools uses 960 ticks per beat
     ticks_per_frame = ticks_per_beat / fps  # Ticks per frame
@@ -25,11 +25,12 @@ def create_midi_with_markers(frame_numbers, output_filename, fps=25, bpm=60):
     for i, frame in enumerate(adjusted_frame_numbers):
         # Calculate the tick difference from the previous frame
         if i == 0:
-            tick_diff = 0
+            tick_diff = frame  # The first marker doesn't have to be at the start
         else:
             tick_diff = frame - adjusted_frame_numbers[i - 1]
         
-        marker_text = f'SC {i + 1}'  # Custom marker text for each scene
+        # Custom marker text for each scene, scene labels take precedence
+        marker_text = marker_texts[i] if marker_texts and marker_texts[i] else f'SC {i + 1}'
         # Add a marker (meta event) at the scene change time
         track.append(MetaMessage('marker', text=marker_text, time=tick_diff))
         previous_tick += tick_diff
@@ -0,0 +1,17 @@
+class DetectionObserver:
+    # Override any of these to follow a detection run, e.g. for custom telemetry.
+    # The defaults do nothing.
+
+    def on_frame_scored(self, frame_number, score):
+        pass
+
+    # Called for each cut once the whole run is done, after flash filtering, confirmation
+    # and offsets, not while frames are being processed. The opening scene isn't a cut.
+    def on_cut(self, frame_number):
+        pass
+
+    def on_warning(self, message):
+        pass
+
+    def on_complete(self, frame_numbers, report):
+        pass
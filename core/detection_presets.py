@@ -0,0 +1,16 @@
+# Vetted option combinations for detect_scene_changes, from fastest to most accurate
+DETECTION_PRESETS = {
+    # Score heavily downscaled frames, then re-check the candidate cuts at full resolution
+    'fast': {'downscale': 8, 'confirm_full_resolution': True},
+    # PySceneDetect's automatic downscaling for the video resolution
+    'balanced': {},
+    # Score full resolution frames, with noise filtering
+    'accurate': {'downscale': 1, 'blur_kernel_size': 5},
+}
+
+def resolve_preset(preset, **options):
+    # Start from the preset's options, letting explicitly given ones take precedence
+    if preset not in DETECTION_PRESETS:
+        raise ValueError(f"Unknown detection preset '{preset}', available presets are: {', '.join(DETECTION_PRESETS)}.")
+
+    return {**DETECTION_PRESETS[preset], **options}
@@ -0,0 +1,53 @@
+import ctypes
+import logging
+import sys
+from dataclasses import dataclass, field
+from typing import Optional
+
+@dataclass
+class DetectionReport:
+    total_frames: int = 0  # Frame count from the video metadata
+    frames_processed: int = 0
+    frames_skipped: int = 0  # Frames left unprocessed because a limit was hit
+    elapsed_time: float = 0.0  # Wall-clock seconds
+    average_fps: float = 0.0  # End-to-end
+    decode_fps: float = 0.0  # Frames per second spent decoding, on the decode thread
+    analyze_fps: float = 0.0  # Frames per second spent scoring
+    peak_memory_mb: Optional[float] = None  # Peak resident memory of the whole process
+    truncated: bool = False
+    backend: str = ''  # Video backend that opened the file
+    timings: dict = field(default_factory=dict)  # Seconds spent in each stage
+    configuration: dict = field(default_factory=dict)
+    warnings: list = field(default_factory=list)
+
+def report_warning(report, message, observer=None):
+    # Log the warning and keep it on the report when there is one
+    logging.warning(message)
+    if report is not None:
+        report.warnings.append(message)
+    if observer is not None:
+        observer.on_warning(message)
+
+def get_peak_memory_mb():
+    # Peak resident set size of the process, None where it can't be read
+    if sys.platform == 'win32':
+        class ProcessMemoryCounters(ctypes.Structure):
+            _fields_ = [('cb', ctypes.c_ulong), ('PageFaultCount', ctypes.c_ulong)] + [
+                (name, ctypes.c_size_t) for name in ('PeakWorkingSetSize', 'WorkingSetSize', 'QuotaPeakPagedPoolUsage',
+                                                     'QuotaPagedPoolUsage', 'QuotaPeakNonPagedPoolUsage',
+                                                     'QuotaNonPagedPoolUsage', 'PagefileUsage', 'PeakPagefileUsage')]
+        counters = ProcessMemoryCounters()
+        counters.cb = ctypes.sizeof(counters)
+        process = ctypes.windll.kernel32.GetCurrentProcess()
+        if not ctypes.windll.psapi.GetProcessMemoryInfo(process, ctypes.byref(counters), counters.cb):
+            return None
+        return counters.PeakWorkingSetSize / (1024 * 1024)
+
+    try:
+        import resource
+    except ImportError:
+        return None
+
+    # Linux reports kilobytes, macOS bytes
+    peak = resource.getrusage(resource.RUSAGE_SELF).ru_maxrss
+    return peak / (1024 * 1024) if sys.platform == 'darwin' else peak / 1024
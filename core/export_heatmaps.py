@@ -0,0 +1,33 @@
+import os
+
+import cv2
+
+from core.format_file_name import format_file_name
+from core.frame_difference import read_frame_pair
+
+def export_difference_heatmaps(video_path, frame_numbers, output_path, frames_around=1, file_name_template='heatmap_$FRAME_NUMBER.$EXT'):
+    os.makedirs(output_path, exist_ok=True)
+    video_capture = cv2.VideoCapture(video_path)
+    fps = video_capture.get(cv2.CAP_PROP_FPS)
+
+    heatmap_paths = []
+    for frame_number in frame_numbers:
+        # Look at the frames around each cut (or near miss), not just the cut itself
+        for heatmap_frame in range(max(frame_number - frames_around, 1), frame_number + frames_around + 1):
+            previous_frame, current_frame = read_frame_pair(video_capture, heatmap_frame)
+            if previous_frame is None:
+                continue
+
+            # Per-pixel change, strongest channel, stretched to the full color map range
+            difference = cv2.absdiff(previous_frame, current_frame).max(axis=2)
+            difference = cv2.normalize(difference, None, 0, 255, cv2.NORM_MINMAX)
+            heatmap = cv2.applyColorMap(difference, cv2.COLORMAP_JET)
+
+            heatmap_file_name = format_file_name(file_name_template, video_path, frame_number=heatmap_frame, fps=fps, extension='png')
+            heatmap_path = os.path.join(output_path, heatmap_file_name)
+            cv2.imwrite(heatmap_path, heatmap)
+            heatmap_paths.append(heatmap_path)
+
+    video_capture.release()
+
+    return heatmap_paths
@@ -0,0 +1,41 @@
+import os
+import pathlib
+import xml.etree.ElementTree as ET
+
+XSPF_NAMESPACE = 'http://xspf.org/ns/0/'
+VLC_NAMESPACE = 'http://www.videolan.org/vlc/playlist/ns/0/'
+VLC_APPLICATION = 'http://www.videolan.org/vlc/playlist/0'
+
+def export_to_xspf(playlist_path, video_path, scenes, fps):
+    ET.register_namespace('', XSPF_NAMESPACE)
+    ET.register_namespace('vlc', VLC_NAMESPACE)
+    playlist = ET.Element(f'{{{XSPF_NAMESPACE}}}playlist', version='1')
+    track_list = ET.SubElement(playlist, f'{{{XSPF_NAMESPACE}}}trackList')
+
+    # One track per scene, all pointing at the source file, which VLC starts
+    # and stops at the scene boundaries
+    location = pathlib.Path(os.path.abspath(video_path)).as_uri()
+    for scene in scenes:
+        track = ET.SubElement(track_list, f'{{{XSPF_NAMESPACE}}}track')
+        ET.SubElement(track, f'{{{XSPF_NAMESPACE}}}location').text = location
+        ET.SubElement(track, f'{{{XSPF_NAMESPACE}}}title').text = scene.label or f"Scene {scene.index + 1}"
+        ET.SubElement(track, f'{{{XSPF_NAMESPACE}}}duration').text = str(round(scene.duration * 1000))
+
+        extension = ET.SubElement(track, f'{{{XSPF_NAMESPACE}}}extension', application=VLC_APPLICATION)
+        ET.SubElement(extension, f'{{{VLC_NAMESPACE}}}option').text = f"start-time={scene.start / fps:.3f}"
+        ET.SubElement(extension, f'{{{VLC_NAMESPACE}}}option').text = f"stop-time={scene.end / fps:.3f}"
+
+    ET.ElementTree(playlist).write(playlist_path, encoding='utf-8', xml_declaration=True)
+
+def export_to_mpv_edl(edl_path, video_path, scenes, fps):
+    # Paths are prefixed with their length in bytes, so commas in them are safe
+    absolute_path = os.path.abspath(video_path)
+    escaped_path = f"%{len(absolute_path.encode('utf-8'))}%{absolute_path}"
+
+    # Each scene is a segment of the source file, which mpv shows as a chapter
+    lines = ['# mpv EDL v0']
+    for scene in scenes:
+        lines.append(f"{escaped_path},{scene.start / fps:.3f},{scene.duration:.3f}")
+
+    with open(edl_path, 'w', encoding='utf-8') as edl_file:
+        edl_file.write('\n'.join(lines) + '\n')
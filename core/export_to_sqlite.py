@@ -0,0 +1,70 @@
+import json
+import sqlite3
+
+from core.scenes import Scene
+
+SCHEMA = '''
+CREATE TABLE IF NOT EXISTS scenes (
+    video_path TEXT NOT NULL,
+    scene_index INTEGER NOT NULL,
+    start_frame INTEGER NOT NULL,
+    end_frame INTEGER NOT NULL,
+    duration REAL NOT NULL,
+    label TEXT,
+    tags TEXT NOT NULL DEFAULT '[]',
+    notes TEXT NOT NULL DEFAULT '',
+    PRIMARY KEY (video_path, scene_index)
+);
+CREATE TABLE IF NOT EXISTS cuts (
+    video_path TEXT NOT NULL,
+    frame_number INTEGER NOT NULL,
+    PRIMARY KEY (video_path, frame_number)
+);
+CREATE TABLE IF NOT EXISTS frame_metrics (
+    video_path TEXT NOT NULL,
+    frame_number INTEGER NOT NULL,
+    content_val REAL,
+    delta_hue REAL,
+    delta_sat REAL,
+    delta_lum REAL,
+    delta_edges REAL,
+    PRIMARY KEY (video_path, frame_number)
+);
+'''
+
+def export_to_sqlite(database_path, video_path, scenes, frame_metrics=None):
+    connection = sqlite3.connect(database_path)
+    with connection:
+        connection.executescript(SCHEMA)
+
+        # Replace any earlier results for the same video
+        for table in ('scenes', 'cuts', 'frame_metrics'):
+            connection.execute(f'DELETE FROM {table} WHERE video_path = ?', (video_path,))
+
+        # Tags are stored as a JSON list, so they can contain commas
+        connection.executemany(
+            'INSERT INTO scenes VALUES (?, ?, ?, ?, ?, ?, ?, ?)',
+            [(video_path, scene.index, scene.start, scene.end, scene.duration, scene.label, json.dumps(scene.tags), scene.notes)
+             for scene in scenes])
+
+        # Every scene start except the start of the video is a cut
+        connection.executemany(
+            'INSERT INTO cuts VALUES (?, ?)',
+            [(video_path, scene.start) for scene in scenes if scene.start != 0])
+
+        if frame_metrics:
+            connection.executemany(
+                'INSERT INTO frame_metrics VALUES (?, ?, ?, ?, ?, ?, ?)',
+                [(video_path, *metrics) for metrics in frame_metrics])
+
+    connection.close()
+
+def load_scenes_from_sqlite(database_path, video_path):
+    connection = sqlite3.connect(database_path)
+    rows = connection.execute(
+        'SELECT scene_index, start_frame, end_frame, duration, label, tags, notes FROM scenes WHERE video_path = ? ORDER BY scene_index',
+        (video_path,)).fetchall()
+    connection.close()
+
+    return [Scene(index=index, start=start, end=end, duration=duration, label=label, tags=json.loads(tags) if tags else [], notes=notes)
+            for index, start, end, duration, label, tags, notes in rows]
@@ -0,0 +1,19 @@
+import os
+from string import Template
+
+from core.timecode import format_timecode
+
+def format_file_name(template, video_path, scene_number=None, frame_number=None, fps=None, extension='', padding=3):
+    # Fill in $VIDEO_NAME, $SCENE_NUMBER, $FRAME_NUMBER, $TIMECODE and $EXT,
+    # leaving unknown or unavailable placeholders as they are
+    values = {'VIDEO_NAME': os.path.splitext(os.path.basename(video_path))[0], 'EXT': extension}
+    if scene_number is not None:
+        values['SCENE_NUMBER'] = f"{scene_number:0{padding}d}"
+    if frame_number is not None:
+        values['FRAME_NUMBER'] = f"{frame_number:06d}"
+
+        # Colons aren't allowed in Windows file names
+        if fps:
+            values['TIMECODE'] = format_timecode(frame_number, fps).replace(':', '-')
+
+    return Template(template).safe_substitute(values)
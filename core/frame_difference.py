@@ -0,0 +1,95 @@
+import cv2
+import numpy as np
+
+def drop_alpha(frame, premultiply=False):
+    # Screen captures and compositors hand out BGRA frames, the HSV conversion needs BGR
+    if frame.ndim != 3 or frame.shape[2] != 4:
+        return frame
+
+    # Optionally darken transparent pixels first, so hidden content doesn't count
+    if premultiply:
+        alpha = frame[:, :, 3:].astype(np.float32) / 255
+        return (frame[:, :, :3] * alpha).astype(np.uint8)
+
+    return cv2.cvtColor(frame, cv2.COLOR_BGRA2BGR)
+
+def calculate_content_score(previous_frame, current_frame, pixel_stride=1):
+    previous_frame = drop_alpha(previous_frame)
+    current_frame = drop_alpha(current_frame)
+
+    # Sample every Nth row and column to trade accuracy for speed
+    if pixel_stride > 1:
+        previous_frame = np.ascontiguousarray(previous_frame[::pixel_stride, ::pixel_stride])
+        current_frame = np.ascontiguousarray(current_frame[::pixel_stride, ::pixel_stride])
+
+    # Convert both frames to HSV, the same color space PySceneDetect scores in
+    previous_hsv = cv2.cvtColor(previous_frame, cv2.COLOR_BGR2HSV).astype(np.int16)
+    current_hsv = cv2.cvtColor(current_frame, cv2.COLOR_BGR2HSV).astype(np.int16)
+
+    # Mean absolute difference of each channel (hue, saturation, luminance)
+    channel_deltas = np.abs(current_hsv - previous_hsv).mean(axis=(0, 1))
+
+    # Average the channel deltas into a single content score
+    return float(channel_deltas.mean())
+
+def calculate_grid_score(previous_frame, current_frame, rows=4, columns=4, trim=0.25):
+    height, width = current_frame.shape[:2]
+
+    # Score each block of the grid on its own
+    block_scores = []
+    for row in range(rows):
+        for column in range(columns):
+            top, bottom = row * height // rows, (row + 1) * height // rows
+            left, right = column * width // columns, (column + 1) * width // columns
+            previous_block = np.ascontiguousarray(previous_frame[top:bottom, left:right])
+            current_block = np.ascontiguousarray(current_frame[top:bottom, left:right])
+            block_scores.append(calculate_content_score(previous_block, current_block))
+
+    # Trim the highest and lowest block scores so a change confined to a few
+    # blocks, like someone walking through the shot, doesn't count as a cut
+    block_scores.sort()
+    trim_count = int(len(block_scores) * trim)
+    kept_scores = block_scores[trim_count:len(block_scores) - trim_count] or block_scores
+
+    return sum(kept_scores) / len(kept_scores)
+
+def calculate_ssim(previous_frame, current_frame):
+    previous_frame = drop_alpha(previous_frame)
+    current_frame = drop_alpha(current_frame)
+
+    # Structural similarity of the grayscale frames, 1 for identical frames
+    previous_gray = cv2.cvtColor(previous_frame, cv2.COLOR_BGR2GRAY).astype(np.float64)
+    current_gray = cv2.cvtColor(current_frame, cv2.COLOR_BGR2GRAY).astype(np.float64)
+    c1 = (0.01 * 255) ** 2
+    c2 = (0.03 * 255) ** 2
+
+    # Local means, variances and covariance over a Gaussian window
+    blur = lambda image: cv2.GaussianBlur(image, (11, 11), 1.5)
+    previous_mean = blur(previous_gray)
+    current_mean = blur(current_gray)
+    previous_variance = blur(previous_gray * previous_gray) - previous_mean ** 2
+    current_variance = blur(current_gray * current_gray) - current_mean ** 2
+    covariance = blur(previous_gray * current_gray) - previous_mean * current_mean
+
+    ssim_map = ((2 * previous_mean * current_mean + c1) * (2 * covariance + c2)) / (
+        (previous_mean ** 2 + current_mean ** 2 + c1) * (previous_variance + current_variance + c2))
+
+    return float(ssim_map.mean())
+
+def read_frame_pair(video_capture, frame_number):
+    # Seek to the frame before the given one and read both frames
+    video_capture.set(cv2.CAP_PROP_POS_FRAMES, frame_number - 1)
+    previous_ok, previous_frame = video_capture.read()
+    current_ok, current_frame = video_capture.read()
+
+    if not previous_ok or not current_ok:
+        return None, None
+
+    return previous_frame, current_frame
+
+def read_frame(video_capture, frame_number):
+    # Seek to the given frame and read it
+    video_capture.set(cv2.CAP_PROP_POS_FRAMES, frame_number)
+    ok, frame = video_capture.read()
+
+    return frame if ok else None
@@ -0,0 +1,240 @@
+import csv
+import math
+import statistics
+from collections import namedtuple
+
+FrameMetrics = namedtuple('FrameMetrics', ['frame_number', 'content_val', 'delta_hue', 'delta_sat', 'delta_lum', 'delta_edges'])
+
+def load_stats_file(stats_file_path):
+    # Read per-frame metrics from a stats file written by PySceneDetect's StatsManager
+    metrics = {}
+    with open(stats_file_path, newline='') as stats_file:
+        header = None
+        for row in csv.reader(stats_file):
+            if header is None:
+                # Older PySceneDetect versions write a framerate row before the header
+                if row and row[0] == 'Frame Number':
+                    header = row
+                continue
+            if not row:
+                continue
+
+            # PySceneDetect writes 1-based frame numbers, MarkIt uses 0-based ones
+            frame_number = int(row[0]) - 1
+            metrics[frame_number] = {name: float(value) for name, value in zip(header[2:], row[2:]) if value}
+
+    return metrics
+
+def load_scores_file(scores_file_path, frame_column='frame', score_column='score'):
+    # Read a per-frame score series computed elsewhere, with 0-based frame numbers
+    metrics = {}
+    with open(scores_file_path, newline='') as scores_file:
+        for row in csv.DictReader(scores_file):
+            metrics[int(row[frame_column])] = {'content_val': float(row[score_column])}
+
+    # Frames missing from the file score 0
+    return scores_from_metrics(metrics)
+
+def scores_from_metrics(metrics, metric_key='content_val'):
+    # Flatten loaded metrics into a list of scores indexed by frame number
+    num_frames = max(metrics, default=-1) + 1
+    return [metrics.get(frame_number, {}).get(metric_key, 0.0) for frame_number in range(num_frames)]
+
+def frame_metrics_from_stats(metrics):
+    # Convert loaded metrics into FrameMetrics, with missing values as 0
+    return [FrameMetrics(frame_number, *(metrics[frame_number].get(key, 0.0) for key in FrameMetrics._fields[1:])) for frame_number in sorted(metrics)]
+
+def get_scene_change_metrics(frame_numbers, metrics):
+    # Look up the score and per-channel deltas that triggered each scene change
+    return [dict(metrics.get(frame_number, {}), frame_number=frame_number) for frame_number in frame_numbers]
+
+def suppress_flashes(frame_numbers, scores, threshold, window=3, frames_match=None, tolerance=0.25):
+    # A flash or strobe changes the picture and changes it back within a few frames,
+    # so a cut followed within the window by a spike out to a picture matching the one
+    # before the spike in is not a scene change. A hard cut followed by fast motion
+    # also scores high twice, but the picture doesn't change back, so it's kept.
+    # frames_match(before_frame, after_frame) compares the two frames directly. Without
+    # it the scores stand in: the difference into a flash and out of it is the same,
+    # so the spike out has to score about as high as the spike in.
+    if frames_match is None:
+        def frames_match(before_frame, after_frame):
+            spike_in, spike_out = scores[before_frame + 1], scores[after_frame]
+            return abs(spike_in - spike_out) <= tolerance * max(spike_in, spike_out)
+
+    filtered_frame_numbers = []
+    flash_end = -1
+    for frame_number in frame_numbers:
+        # The first scene and cuts without a score are kept as they are
+        if frame_number == 0 or frame_number >= len(scores):
+            filtered_frame_numbers.append(frame_number)
+            continue
+
+        # Cuts inside a flash, e.g. the spike out or the rest of a strobe, go with it
+        if frame_number <= flash_end:
+            continue
+
+        spike_outs = [later_frame for later_frame in range(frame_number + 1, min(frame_number + window + 1, len(scores)))
+                      if scores[later_frame] >= threshold and frames_match(frame_number - 1, later_frame)]
+        if spike_outs:
+            flash_end = spike_outs[-1]
+            continue
+
+        filtered_frame_numbers.append(frame_number)
+
+    return filtered_frame_numbers
+
+def smooth_scores(scores, window=3):
+    # Centered moving average, shorter at the ends where fewer neighbors exist
+    half_window = window // 2
+    smoothed_scores = []
+    for frame_number in range(len(scores)):
+        neighbor_scores = scores[max(frame_number - half_window, 0):frame_number + half_window + 1]
+        smoothed_scores.append(sum(neighbor_scores) / len(neighbor_scores))
+
+    return smoothed_scores
+
+def suggest_threshold_from_scores(scores, deviations=6.0):
+    # Cuts are a tiny minority of frames, which histogram splits like Otsu's method
+    # handle badly, so treat them as outliers instead: scores more than the given
+    # number of (robust) standard deviations above the median score
+    if not scores or max(scores) <= 0:
+        return None
+
+    median = statistics.median(scores)
+    median_absolute_deviation = statistics.median(abs(score - median) for score in scores)
+
+    # Scale the deviation to a standard deviation for normally distributed noise, with a
+    # floor so static content where most frames score the same still gets a margin
+    standard_deviation = max(1.4826 * median_absolute_deviation, 1.0)
+
+    return median + deviations * standard_deviation
+
+def cuts_from_scores(scores, threshold=27.0, min_scene_len=15, merge_transitions=False, low_threshold=None):
+    # The low threshold ends transitions, so it can't be above the one starting them
+    if low_threshold is not None and low_threshold > threshold:
+        raise ValueError(f"Low threshold must not be above the threshold, got {low_threshold} and {threshold}.")
+
+    # Frames scoring above the threshold are cut candidates
+    if merge_transitions:
+        candidates = transition_midpoints(scores, threshold)
+    elif low_threshold is not None:
+        candidates = hysteresis_crossings(scores, threshold, low_threshold)
+    else:
+        candidates = [frame_number for frame_number, score in enumerate(scores) if score >= threshold]
+
+    # Apply the minimum scene length to the candidates
+    cut_frame_numbers = []
+    last_cut = 0
+    for frame_number in candidates:
+        if frame_number - last_cut >= min_scene_len:
+            cut_frame_numbers.append(frame_number)
+            last_cut = frame_number
+
+    return cut_frame_numbers
+
+def hysteresis_crossings(scores, high_threshold, low_threshold):
+    # A cut starts a transition when the score reaches the high threshold, and
+    # the transition only ends once the score drops below the low threshold, so
+    # scores bouncing around the high threshold give a single cut
+    crossings = []
+    in_transition = False
+    for frame_number, score in enumerate(scores):
+        if not in_transition and score >= high_threshold:
+            crossings.append(frame_number)
+            in_transition = True
+        elif in_transition and score < low_threshold:
+            in_transition = False
+
+    return crossings
+
+def transition_midpoints(scores, threshold):
+    # A run of consecutive frames above the threshold, like a dissolve, is one
+    # transition with a single cut in its middle
+    midpoints = []
+    run_start = None
+    for frame_number, score in enumerate(scores):
+        if score >= threshold:
+            if run_start is None:
+                run_start = frame_number
+        elif run_start is not None:
+            midpoints.append((run_start + frame_number - 1) // 2)
+            run_start = None
+
+    if run_start is not None:
+        midpoints.append((run_start + len(scores) - 1) // 2)
+
+    return midpoints
+
+def scene_changes_from_scores(scores, threshold=27.0, min_scene_len=15, flash_window=None, include_start=True,
+                              merge_transitions=False, smoothing_window=None, low_threshold=None):
+    # Same result as detect_scene_changes, computed from cached scores without decoding the video,
+    # optionally smoothed first to damp single-frame noise
+    if smoothing_window:
+        scores = smooth_scores(scores, smoothing_window)
+
+    frame_numbers = [0] + cuts_from_scores(scores, threshold, min_scene_len, merge_transitions, low_threshold)
+
+    # Drop cuts that are camera flashes or strobes
+    if flash_window:
+        frame_numbers = suppress_flashes(frame_numbers, scores, threshold, flash_window)
+
+    if not include_start:
+        frame_numbers = frame_numbers[1:]
+
+    return frame_numbers
+
+def threshold_for_scene_count(scores, target_count, tolerance=1, min_scene_len=15, iterations=30):
+    # Binary search the threshold, raising it while there are too many scenes and
+    # lowering it while there are too few, keeping the closest result seen
+    low_threshold, high_threshold = 0.0, max(scores, default=0.0) + 1
+    best = None
+    for _ in range(iterations):
+        threshold = (low_threshold + high_threshold) / 2
+        frame_numbers = scene_changes_from_scores(scores, threshold, min_scene_len)
+        if best is None or abs(len(frame_numbers) - target_count) < abs(len(best[1]) - target_count):
+            best = (threshold, frame_numbers)
+
+        if len(frame_numbers) > target_count + tolerance:
+            low_threshold = threshold
+        elif len(frame_numbers) < target_count - tolerance:
+            high_threshold = threshold
+        else:
+            break
+
+    return best
+
+def cut_probabilities(scores, threshold=27.0, softness=None):
+    # Map each score to a 0-1 cut likelihood with a logistic curve centered on the
+    # threshold, for fusing with other signals instead of a hard decision
+    softness = softness if softness is not None else threshold / 8
+    if softness <= 0:
+        raise ValueError(f"Softness must be positive, got {softness}, pass it explicitly for a threshold of 0.")
+
+    probabilities = []
+    for score in scores:
+        margin = max(min((score - threshold) / softness, 50), -50)
+        probabilities.append(1 / (1 + math.exp(-margin)))
+
+    return probabilities
+
+def sweep_thresholds(scores, thresholds, min_scene_len=15):
+    # Scene changes at each threshold, all from the same scores
+    return {threshold: scene_changes_from_scores(scores, threshold, min_scene_len) for threshold in thresholds}
+
+def analyze_frame_scores(scores, thresholds=(15, 20, 25, 27, 30, 35, 40), min_scene_len=15):
+    if not scores:
+        return None
+
+    # Nearest-rank percentiles of the score distribution
+    sorted_scores = sorted(scores)
+    def percentile(percent):
+        index = max(round(percent / 100 * len(sorted_scores)) - 1, 0)
+        return sorted_scores[index]
+
+    return {
+        'mean': statistics.mean(scores),
+        'max': sorted_scores[-1],
+        'percentiles': {percent: percentile(percent) for percent in (50, 90, 95, 99)},
+        # Number of cuts each threshold would produce
+        'cut_counts': {threshold: len(cuts_from_scores(scores, threshold, min_scene_len)) for threshold in thresholds},
+    }
@@ -0,0 +1,33 @@
+import cv2
+import scenedetect
+from scenedetect.backends import AVAILABLE_BACKENDS
+
+# Export formats supported by the core modules
+EXPORT_FORMATS = ['midi', 'pyscenedetect_stats_csv', 'sqlite', 'xspf', 'mpv_edl', 'scene_histograms_json', 'heatmap_png']
+
+def get_capabilities():
+    # Everything that decides which files can be opened and how fast, for bug reports
+    return {
+        'pyscenedetect_version': scenedetect.__version__,
+        'opencv_version': cv2.__version__,
+        'video_backends': sorted(AVAILABLE_BACKENDS),
+        'opencv_video_io': get_opencv_video_io(),
+        'opencl_available': cv2.ocl.haveOpenCL(),
+        'cuda_devices': cv2.cuda.getCudaEnabledDeviceCount() if hasattr(cv2, 'cuda') else 0,
+        'export_formats': EXPORT_FORMATS,
+    }
+
+def get_opencv_video_io():
+    # The "Video I/O" section of OpenCV's build information, e.g. {'FFMPEG': 'YES (58.29.100)'}
+    video_io = {}
+    in_section = False
+    for line in cv2.getBuildInformation().splitlines():
+        if line.strip() == 'Video I/O:':
+            in_section = True
+        elif in_section and line.startswith('    ') and ':' in line:
+            name, value = line.split(':', 1)
+            video_io[name.strip()] = value.strip()
+        elif in_section and line.strip():
+            break
+
+    return video_io
@@ -0,0 +1,50 @@
+def get_gif_fps(gif_path):
+    with open(gif_path, 'rb') as gif_file:
+        data = gif_file.read()
+
+    # Frame delays in hundredths of a second, read from the graphic control extensions
+    delays = read_gif_frame_delays(data)
+    if not delays:
+        return None
+
+    # Browsers play delays under 2 hundredths of a second at 10, so do the same
+    delays = [delay if delay >= 2 else 10 for delay in delays]
+
+    return len(delays) / (sum(delays) / 100)
+
+def read_gif_frame_delays(data):
+    if data[:6] not in (b'GIF87a', b'GIF89a'):
+        raise ValueError("Not a GIF file.")
+
+    # Skip the header, logical screen descriptor and global color table
+    position = 13
+    if data[10] & 0x80:
+        position += 3 * 2 ** ((data[10] & 0x07) + 1)
+
+    delays = []
+    while position < len(data):
+        block_type = data[position]
+        if block_type == 0x21:
+            # Extension block, graphic control extensions hold the frame delay
+            if data[position + 1] == 0xF9:
+                delays.append(int.from_bytes(data[position + 4:position + 6], 'little'))
+            position = skip_sub_blocks(data, position + 2)
+        elif block_type == 0x2C:
+            # Image descriptor, followed by an optional local color table and the image data
+            packed = data[position + 9]
+            position += 10
+            if packed & 0x80:
+                position += 3 * 2 ** ((packed & 0x07) + 1)
+            position = skip_sub_blocks(data, position + 1)
+        else:
+            # Trailer or unknown data
+            break
+
+    return delays
+
+def skip_sub_blocks(data, position):
+    # Sub-blocks start with their size and end with an empty block
+    while position < len(data) and data[position] != 0:
+        position += data[position] + 1
+
+    return position + 1
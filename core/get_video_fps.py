@@ -1,6 +1,16 @@
+import math
+
 import cv2
 
-def get_video_fps(video_path):
+from core.get_gif_fps import get_gif_fps
+
+def get_video_fps(video_path, fallback_fps=None):
+    # Animated GIFs store per-frame delays instead of a frame rate
+    if video_path.lower().endswith('.gif'):
+        gif_fps = get_gif_fps(video_path)
+        if gif_fps:
+            return gif_fps
+
     # Open the video file
     video_capture = cv2.VideoCapture(video_path)
     
@@ -9,5 +19,13 @@ def get_video_fps(video_path):
     
     # Release the video capture object
     video_capture.release()
+
+    # Use the fallback when the metadata can't be trusted
+    if fallback_fps and not is_valid_fps(fps):
+        return fallback_fps
     
-    return fps
\ No newline at end of file
+    return fps
+
+def is_valid_fps(fps):
+    # WebM files and pipes commonly report 0, NaN or 1000 FPS
+    return not math.isnan(fps) and 0 < fps < 1000
\ No newline at end of file
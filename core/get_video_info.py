@@ -0,0 +1,31 @@
+import cv2
+
+def get_video_info(video_path):
+    # Open the video file
+    video_capture = cv2.VideoCapture(video_path)
+
+    # Get the basic video properties
+    info = {
+        'width': int(video_capture.get(cv2.CAP_PROP_FRAME_WIDTH)),
+        'height': int(video_capture.get(cv2.CAP_PROP_FRAME_HEIGHT)),
+        'fps': video_capture.get(cv2.CAP_PROP_FPS),
+        'frame_count': int(video_capture.get(cv2.CAP_PROP_FRAME_COUNT)),
+        'codec': decode_fourcc(video_capture.get(cv2.CAP_PROP_FOURCC)),
+        'pixel_format': decode_fourcc(video_capture.get(cv2.CAP_PROP_CODEC_PIXEL_FORMAT)),
+        # Rotation metadata is only available in OpenCV 4.5 and newer, which also
+        # rotates decoded frames by it and reports the rotated width and height
+        'rotation': int(video_capture.get(cv2.CAP_PROP_ORIENTATION_META)) if hasattr(cv2, 'CAP_PROP_ORIENTATION_META') else 0,
+    }
+
+    # Release the video capture object
+    video_capture.release()
+
+    return info
+
+def decode_fourcc(value):
+    # OpenCV returns FOURCC codes as a float, with one character per byte
+    code = int(value)
+    if code <= 0:
+        return None
+
+    return ''.join(chr((code >> (8 * i)) & 0xFF) for i in range(4)).strip('\x00 ')
@@ -0,0 +1,31 @@
+import cv2
+
+# Where in the scene to grab the frame from, as a fraction of its length
+FRAME_POSITIONS = {'start': 0.0, 'middle': 0.5, 'end': 1.0}
+
+def grab_scene_frame(video, scene, position='middle', image_format='jpg', max_width=None):
+    # Accept a video path or an already opened cv2.VideoCapture, e.g. for many thumbnails
+    video_capture = cv2.VideoCapture(video) if isinstance(video, str) else video
+
+    # Position is 'start', 'middle', 'end' or a fraction of the scene; 'end' is the last frame
+    fraction = FRAME_POSITIONS[position] if isinstance(position, str) else position
+    frame_number = scene.start + round(fraction * max(scene.end - scene.start - 1, 0))
+
+    video_capture.set(cv2.CAP_PROP_POS_FRAMES, frame_number)
+    ok, frame = video_capture.read()
+    if isinstance(video, str):
+        video_capture.release()
+    if not ok:
+        return None
+
+    # Shrink the frame for thumbnails if requested
+    height, width = frame.shape[:2]
+    if max_width and width > max_width:
+        frame = cv2.resize(frame, (max_width, round(height * max_width / width)), interpolation=cv2.INTER_AREA)
+
+    # Encode the frame as JPEG or PNG bytes
+    ok, encoded_image = cv2.imencode(f'.{image_format}', frame)
+    if not ok:
+        raise ValueError(f"Can't encode the frame as '{image_format}'.")
+
+    return encoded_image.tobytes()
@@ -0,0 +1,37 @@
+import bisect
+import statistics
+
+def match_scene_changes(frame_numbers_a, fps_a, frame_numbers_b, fps_b, tolerance=0.5):
+    # Compare cuts by time, since the two encodes can have different frame rates
+    times_a = sorted(frame / fps_a for frame in frame_numbers_a)
+    times_b = sorted(frame / fps_b for frame in frame_numbers_b)
+
+    # Estimate the constant offset between encodes from the nearest cut pairs
+    offset = 0.0
+    if times_a and times_b:
+        offset = statistics.median(nearest(times_b, time) - time for time in times_a)
+
+    # Pair up cuts that line up within the tolerance once the offset is removed
+    matched = []
+    unmatched_b = list(times_b)
+    missing_in_b = []
+    for time in times_a:
+        candidate = nearest(unmatched_b, time + offset) if unmatched_b else None
+        if candidate is not None and abs(candidate - offset - time) <= tolerance:
+            matched.append((round(time * fps_a), round(candidate * fps_b)))
+            unmatched_b.remove(candidate)
+        else:
+            missing_in_b.append(round(time * fps_a))
+
+    return {
+        'offset': offset,  # Seconds to add to times in A to get times in B
+        'matched': matched,
+        'missing_in_b': missing_in_b,
+        'missing_in_a': [round(time * fps_b) for time in unmatched_b],
+    }
+
+def nearest(sorted_times, time):
+    # Closest value to the given time in a sorted list
+    index = bisect.bisect_left(sorted_times, time)
+    candidates = sorted_times[max(index - 1, 0):index + 1]
+    return min(candidates, key=lambda candidate: abs(candidate - time))
@@ -0,0 +1,20 @@
+from core.timecode import parse_timecode
+
+def merge_scene_changes(frame_numbers, extra_frame_numbers, tolerance=5):
+    # Known cuts replace detected ones within the tolerance, since they are exact
+    merged = [frame for frame in frame_numbers
+              if not any(abs(frame - extra_frame) <= tolerance for extra_frame in extra_frame_numbers)]
+
+    return sorted(set(merged + list(extra_frame_numbers)))
+
+def load_extra_cuts(extra_cuts_path, fps):
+    # One cut per line, as a frame number or an HH:MM:SS.mmm timecode
+    extra_frame_numbers = []
+    with open(extra_cuts_path) as extra_cuts_file:
+        for line in extra_cuts_file:
+            line = line.strip()
+            if not line or line.startswith('#'):
+                continue
+            extra_frame_numbers.append(int(line) if line.isdigit() else parse_timecode(line, fps))
+
+    return extra_frame_numbers
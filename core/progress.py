@@ -0,0 +1,54 @@
+import time
+from collections import namedtuple
+
+from core.detection_report import get_peak_memory_mb
+
+# fps is end to end, decode_fps and analyze_fps are only known with stage times
+Progress = namedtuple('Progress', ['frames_processed', 'total_frames', 'percent', 'fps', 'eta_seconds',
+                                   'decode_fps', 'analyze_fps', 'peak_memory_mb'], defaults=(None, None, None))
+
+class ProgressTracker:
+    def __init__(self, total_frames, callback, report_every=25, smoothing=0.2, clock=time.perf_counter, stage_times=None):
+        self.total_frames = total_frames
+        self.callback = callback
+        self.report_every = report_every  # Frames between reports
+        self.smoothing = smoothing  # Weight of the newest rate in the moving average
+        self.clock = clock  # Seconds, perf_counter has a finer resolution than monotonic on Windows
+        self.stage_times = stage_times  # Returns the seconds spent decoding and analyzing so far
+        self.frames_processed = 0
+        self.fps = None
+        self.last_report_frames = 0
+        self.last_report_time = self.clock()
+
+    def update(self, frames=1):
+        self.frames_processed += frames
+        if self.frames_processed - self.last_report_frames < self.report_every and self.frames_processed < self.total_frames:
+            return
+
+        # Smooth the processing rate so the estimate doesn't jump around between reports
+        now = self.clock()
+        elapsed = now - self.last_report_time
+        frames_since_report = self.frames_processed - self.last_report_frames
+        self.last_report_time = now
+        self.last_report_frames = self.frames_processed
+        if elapsed > 0:
+            current_fps = frames_since_report / elapsed
+            if self.fps is None:
+                self.fps = current_fps
+            else:
+                self.fps = self.smoothing * current_fps + (1 - self.smoothing) * self.fps
+
+        # Estimate the remaining time from the smoothed rate
+        remaining_frames = max(self.total_frames - self.frames_processed, 0)
+        eta_seconds = remaining_frames / self.fps if self.fps else None
+        percent = 100.0 * self.frames_processed / self.total_frames if self.total_frames else 0.0
+
+        # Average rates of each stage so far
+        decode_fps = analyze_fps = None
+        if self.stage_times:
+            decode_time, analysis_time = self.stage_times()
+            decode_fps = self.frames_processed / decode_time if decode_time > 0 else None
+            analyze_fps = self.frames_processed / analysis_time if analysis_time > 0 else None
+
+        self.callback(Progress(self.frames_processed, self.total_frames, min(percent, 100.0), self.fps, eta_seconds,
+                               decode_fps, analyze_fps, get_peak_memory_mb()))
@@ -1,11 +1,511 @@
 # scene_detection.py
-from scenedetect import ContentDetector, detect
+import collections
+import inspect
+import logging
+import numbers
+import os
+import threading
+import time
+from concurrent.futures import ThreadPoolExecutor
 
-def detect_scene_changes(video_path):
-    # Get list of scene boundaries
-    scene_list = detect(video_path, ContentDetector())
+import cv2
+import numpy as np
+from scenedetect import SceneManager, StatsManager, open_video
+
+from core.content_detector import MarkItContentDetector
+from core.detection_presets import resolve_preset
+from core.detection_report import DetectionReport, get_peak_memory_mb, report_warning
+from core.frame_difference import calculate_grid_score, read_frame, read_frame_pair
+from core.frame_scores import FrameMetrics, analyze_frame_scores, cut_probabilities, suggest_threshold_from_scores, suppress_flashes, sweep_thresholds, threshold_for_scene_count
+from core.get_gif_fps import get_gif_fps
+from core.get_video_fps import get_video_fps, is_valid_fps
+from core.get_video_info import get_video_info
+from core.progress import ProgressTracker
+from core.scene_histograms import export_scene_histograms, get_scene_histograms
+from core.scene_report import check_cut_rate
+from core.ssim_detector import SsimDetector
+from core.validate_video import validate_video
+from core.weight_presets import resolve_weights
+
+def detect_scene_changes(video_path, threshold=27.0, min_scene_len=15, downscale=None, confirm_full_resolution=False,
+                         pixel_stride=1, progress_callback=None, frame_callback=None, stats_file_path=None,
+                         blur_kernel_size=None, flash_window=None, ignore_regions=None, timeout=None,
+                         max_frames=None, include_start=True, custom_metrics=None, grid_size=None, ssim_threshold=None,
+                         max_decode_attempts=5, framerate=None, fallback_fps=None, start_time=None, end_time=None,
+                         weights='default', backend='opencv', cut_offset=0, cooldown_frames=0,
+                         histograms_file_path=None, observer=None, video=None, report=None):
+    started_at = time.monotonic()
+    whole_video = video is None and start_time is None and end_time is None
+
+    if video is None:
+        # OpenCV's checks only apply to its own backend, the others read files it can't
+        if backend == 'opencv':
+            # Fail early with a clear error for files that aren't videos
+            validate_video(video_path)
+        elif not os.path.isfile(video_path):
+            raise FileNotFoundError(f"Video file '{video_path}' does not exist.")
+
+        # Animated GIFs get their frame rate from the frame delays, not OpenCV's guess
+        if framerate is None and video_path.lower().endswith('.gif'):
+            framerate = get_gif_fps(video_path)
+
+        # Override the frame rate, or fall back to the given one if the metadata is unusable
+        def get_fallback_fps():
+            if fallback_fps:
+                report_warning(report, f"Video frame rate metadata is invalid, using {fallback_fps} FPS instead.", observer)
+            else:
+                report_warning(report, "Video frame rate metadata is invalid, timecodes may be inaccurate. Set a frame rate to override it.", observer)
+            return fallback_fps
+
+        # The OpenCV backend can't open a video without a usable frame rate, so its
+        # metadata is checked up front
+        if framerate is None and backend == 'opencv' and not is_valid_fps(get_video_fps(video_path)):
+            framerate = get_fallback_fps()
+
+        # The OpenCV backend skips frames that fail to decode, and only gives up
+        # after this many consecutive failures. Other backends (pyav, moviepy)
+        # handle some problem files better but don't take that option.
+        backend_options = {'max_decode_attempts': max_decode_attempts} if backend == 'opencv' else {}
+        video = open_video(video_path, framerate=framerate, backend=backend, **backend_options)
+
+        # Other backends report their own frame rate once the video is open
+        if framerate is None and backend != 'opencv' and not is_valid_fps(video.frame_rate):
+            framerate = get_fallback_fps()
+            if framerate:
+                video = open_video(video_path, framerate=framerate, backend=backend)
+
+        # Start from the given time (seconds, frame number or timecode) if requested
+        if start_time is not None:
+            video.seek(start_time)
+    else:
+        # Run on the caller's already opened stream, from its current position
+        video_path = video.path
+
+    # Report progress with throughput and time estimates if requested, and the time
+    # spent in each stage once detection has started
+    def get_stage_times():
+        analysis_time = content_detector.processing_time - content_detector.callback_time
+        if ssim_detector:
+            analysis_time += ssim_detector.processing_time
+        return decode_time, analysis_time
+    on_frame_processed = None
+    if progress_callback:
+        on_frame_processed = ProgressTracker(video.duration.get_frames(), progress_callback, stage_times=get_stage_times).update
+
+    # Collect per-frame metrics only when they need to be saved, filtered on or passed on
+    stats_manager = StatsManager() if stats_file_path or flash_window or frame_callback else None
+
+    # Blend in a block-based score that ignores changes confined to part of the frame
+    if grid_size:
+        rows, columns = grid_size
+        grid_metric = lambda previous_frame, current_frame: calculate_grid_score(previous_frame, current_frame, rows, columns)
+        custom_metrics = (custom_metrics or []) + [(grid_metric, 1.0)]
+
+    # Scoring settings shared by the detector and the checks on frames read back later,
+    # so those are made on the same metric that found the cuts
+    scoring_options = dict(
+        blur_kernel_size=blur_kernel_size,
+        ignore_regions=ignore_regions,
+        frame_width=video.frame_size[0],
+        custom_metrics=custom_metrics,
+        pixel_stride=pixel_stride,
+        weights=resolve_weights(weights),
+        threshold=threshold)
+
+    # The frame callback gets full-resolution frames, the detectors downscaled ones
+    decoded_frames = collections.deque() if frame_callback else None
+    scene_manager = SceneManager(stats_manager)
+    content_detector = MarkItContentDetector(
+        on_frame_processed=on_frame_processed,
+        on_frame_scored=observer.on_frame_scored if observer else None,
+        frame_callback=frame_callback,
+        decoded_frames=decoded_frames,
+        cooldown_frames=cooldown_frames,
+        collect_histograms=bool(histograms_file_path),
+        min_scene_len=min_scene_len,
+        **scoring_options)
+    scene_manager.add_detector(content_detector)
+
+    # Add SSIM as a second opinion, cuts found by either detector are reported
+    ssim_detector = SsimDetector(threshold=ssim_threshold, min_scene_len=min_scene_len) if ssim_threshold else None
+    if ssim_detector:
+        scene_manager.add_detector(ssim_detector)
+
+    # Use a fixed downscale factor instead of the automatic one if requested
+    if downscale:
+        scene_manager.auto_downscale = False
+        scene_manager.downscale = downscale
+
+    # Stop detection once the wall-clock time limit is reached
+    timed_out = threading.Event()
+    def stop_detection():
+        timed_out.set()
+        scene_manager.stop()
+    timer = threading.Timer(timeout, stop_detection) if timeout else None
+    if timer:
+        timer.daemon = True
+        timer.start()
+
+    # Get list of scene boundaries. Frame numbers count from the start of the video,
+    # even when detection starts later, e.g. on a seeked stream.
+    start_frame = video.frame_number
+    detection_started_at = time.monotonic()
+
+    # SceneManager decodes on its own thread, so time each read there, and keep each
+    # frame as it is read until the content detector takes it for the frame callback
+    decode_time = 0.0
+    first_frame_size = last_frame_size = None
+    resolution_changes = []
+    read_frame = video.read
+    def read_and_keep_frame(*args, **kwargs):
+        nonlocal decode_time, first_frame_size, last_frame_size
+        read_started_at = time.monotonic()
+        frame = read_frame(*args, **kwargs)
+        decode_time += time.monotonic() - read_started_at
+        if not isinstance(frame, np.ndarray):
+            return frame
+        if frame_callback:
+            decoded_frames.append(frame)
+
+        # The detectors compare frames of one size, so frames of a stream that changes
+        # resolution midway, e.g. between renditions, are scaled back to the first size
+        frame_size = (frame.shape[1], frame.shape[0])
+        if first_frame_size is None:
+            first_frame_size = last_frame_size = frame_size
+        if frame_size != last_frame_size:
+            resolution_changes.append((video.frame_number - 1, frame_size))
+            last_frame_size = frame_size
+        if frame_size != first_frame_size:
+            frame = cv2.resize(frame, first_frame_size, interpolation=cv2.INTER_AREA)
+        return frame
+    video.read = read_and_keep_frame
+
+    try:
+        num_frames = scene_manager.detect_scenes(video, duration=max_frames, end_time=end_time)
+    finally:
+        if timer:
+            timer.cancel()
+        del video.read
+    detection_time = time.monotonic() - detection_started_at
+
+    # Partial results are still returned when a limit was hit
+    truncated = False
+    if timed_out.is_set():
+        truncated = True
+        report_warning(report, f"Detection timed out after {timeout} seconds, results only cover the first {num_frames} frames.", observer)
+    elif max_frames and num_frames >= max_frames:
+        truncated = True
+        report_warning(report, f"Detection stopped at the {max_frames} frame limit, results only cover the first {num_frames} frames.", observer)
+
+    # Record frames that were skipped because they couldn't be decoded
+    decode_failures = getattr(video, '_decode_failures', 0)
+    if decode_failures:
+        report_warning(report, f"Failed to decode {decode_failures} frames, results around them may be inaccurate.", observer)
+
+    # Frames after a resolution change were scaled, which can shift their scores
+    for frame_number, (width, height) in resolution_changes:
+        report_warning(report, f"Video resolution changes to {width}x{height} at frame {frame_number}, frames are scored at "
+                               f"{first_frame_size[0]}x{first_frame_size[1]} and results around the change may be inaccurate.", observer)
+
+    # The frame count in the metadata is only an estimate for some containers
+    total_frames = video.duration.get_frames()
+    if whole_video and not truncated and num_frames != total_frames:
+        report_warning(report, f"Video metadata reports {total_frames} frames but {num_frames} were decoded, the frame count is an estimate.", observer)
+
+    # Start in a scene so a video without cuts still has its opening scene
+    scene_list = scene_manager.get_scene_list(start_in_scene=True)
+
+    # Save per-frame metrics in PySceneDetect's stats file format
+    if stats_file_path:
+        stats_manager.save_to_csv(csv_file=stats_file_path)
 
     # Extract frame numbers
     frame_numbers = [scene[0].get_frames() for scene in scene_list]
-    
-    return frame_numbers
\ No newline at end of file
+    filtering_started_at = time.monotonic()
+
+    # Drop cuts that are camera flashes or strobes, keeping the opening scene
+    if flash_window:
+        scores = get_frame_scores(stats_manager, num_frames, start_frame=start_frame)
+        video_capture = cv2.VideoCapture(video_path)
+        frames_match = lambda before_frame, after_frame: frames_match_in_video(video_capture, before_frame, after_frame, **scoring_options)
+        frame_numbers = frame_numbers[:1] + suppress_flashes(frame_numbers[1:], scores, threshold, flash_window, frames_match)
+        video_capture.release()
+
+    # Re-check candidates found on downscaled frames at full resolution, keeping the opening scene
+    if confirm_full_resolution:
+        frame_numbers = frame_numbers[:1] + confirm_scene_changes(video_path, frame_numbers[1:], **scoring_options)
+
+    # Shift the cuts, e.g. by -1 for tools that mark the last frame of the previous scene,
+    # but keep the opening scene where detection started and the cuts after it
+    unshifted_frame_numbers = frame_numbers
+    if cut_offset and frame_numbers:
+        # Integers are frames and floats seconds, even whole ones, as for start_time
+        offset_frames = int(cut_offset) if isinstance(cut_offset, numbers.Integral) else round(cut_offset * video.frame_rate)
+        cut_frame_numbers = frame_numbers
+        opening_frame = cut_frame_numbers[0]
+        last_frame = start_frame + num_frames - 1
+        unshifted_frame_numbers, frame_numbers = [opening_frame], [opening_frame]
+        for frame_number in cut_frame_numbers[1:]:
+            # Cuts stay after the opening scene and within the frames detected on, and
+            # cuts clamped onto the same frame become one
+            shifted_frame_number = min(max(frame_number + offset_frames, opening_frame + 1), last_frame)
+            if shifted_frame_number > frame_numbers[-1]:
+                unshifted_frame_numbers.append(frame_number)
+                frame_numbers.append(shifted_frame_number)
+
+    # Save the average color histogram of each final scene, including the opening one,
+    # combined from the histograms collected between the unshifted cuts
+    if histograms_file_path:
+        scene_histograms = get_scene_histograms(content_detector.scene_histogram_sums, unshifted_frame_numbers)
+        shifted_starts = dict(zip(unshifted_frame_numbers, frame_numbers))
+        export_scene_histograms(histograms_file_path, [(shifted_starts[start], histogram) for start, histogram in scene_histograms])
+
+    # The start of the video, or where detection started, is reported as the first
+    # scene change unless disabled
+    if not include_start:
+        frame_numbers = frame_numbers[1:]
+    filtering_time = time.monotonic() - filtering_started_at
+
+    # Flag cut rates that suggest a misconfigured threshold
+    cut_rate_warning = check_cut_rate(frame_numbers, num_frames, video.frame_rate)
+    if cut_rate_warning:
+        report_warning(report, cut_rate_warning, observer)
+
+    # Fill in the run statistics if a report was requested
+    if report is not None:
+        report.total_frames = total_frames
+        report.frames_processed = num_frames
+        report.frames_skipped = max(report.total_frames - num_frames, 0) if truncated else 0
+        report.elapsed_time = time.monotonic() - started_at
+        report.average_fps = num_frames / report.elapsed_time if report.elapsed_time > 0 else 0.0
+        report.truncated = truncated
+        report.backend = video.BACKEND_NAME
+
+        # Decoding overlaps with analysis on its own thread, so the rest of the detection
+        # time is spent waiting for decoded frames, not decoding
+        content_time = content_detector.processing_time - content_detector.callback_time
+        ssim_time = ssim_detector.processing_time if ssim_detector else 0.0
+        analysis_time = content_time + ssim_time
+        queue_wait_time = max(detection_time - content_detector.processing_time - ssim_time, 0.0)
+        report.decode_fps = num_frames / decode_time if decode_time > 0 else 0.0
+        report.analyze_fps = num_frames / analysis_time if analysis_time > 0 else 0.0
+        report.peak_memory_mb = get_peak_memory_mb()
+
+        # Scoring includes the color conversion
+        report.timings = {
+            'decode': decode_time,
+            'queue_wait': queue_wait_time,
+            'preprocess': content_detector.preprocessing_time,
+            'score': content_time - content_detector.preprocessing_time,
+            'ssim': ssim_time,
+            'callbacks': content_detector.callback_time,
+            'filter': filtering_time,
+        }
+
+    # Let the observer know about the final cuts, after all filtering. The opening
+    # scene, if included, starts where detection started and isn't a cut.
+    if observer:
+        for frame_number in (frame_numbers[1:] if include_start else frame_numbers):
+            observer.on_cut(frame_number)
+        observer.on_complete(frame_numbers, report)
+
+    return frame_numbers
+
+def detect_stream(video, **options):
+    # Detect scene changes in an already opened, and possibly seeked, PySceneDetect VideoStream
+    return detect_scene_changes(video.path, video=video, **options)
+
+def detect_with_report(video_path, **options):
+    # Record the full configuration, including defaults, but not callbacks
+    arguments = inspect.signature(detect_scene_changes).bind(video_path, **options)
+    arguments.apply_defaults()
+    configuration = {name: value for name, value in arguments.arguments.items()
+                     if name not in ('video_path', 'progress_callback', 'frame_callback', 'custom_metrics', 'observer', 'video', 'report')}
+
+    report = DetectionReport(configuration=configuration)
+    frame_numbers = detect_scene_changes(video_path, report=report, **options)
+
+    return frame_numbers, report
+
+def detect_with_preset(video_path, preset='balanced', **options):
+    # Detect scene changes with a named speed/accuracy preset, e.g. 'fast'
+    return detect_scene_changes(video_path, **resolve_preset(preset, **options))
+
+def detect_scene_changes_excluding(video_path, excluded_ranges, include_start=True, **options):
+    # Detect each part of the video between the excluded (start, end) ranges, in
+    # seconds, separately so detector state doesn't carry across an excluded range.
+    # Bounds are floats since PySceneDetect takes an int as a frame number.
+    segments = []
+    segment_start = 0.0
+    for excluded_start, excluded_end in sorted(excluded_ranges):
+        excluded_start, excluded_end = float(excluded_start), float(excluded_end)
+        if excluded_start > segment_start:
+            segments.append((segment_start, excluded_start))
+        segment_start = max(segment_start, excluded_end)
+    segments.append((segment_start, None))
+
+    frame_numbers = [0] if include_start else []
+    for segment_start, segment_end in segments:
+        segment_frame_numbers = detect_scene_changes(video_path, start_time=segment_start, end_time=segment_end, **options)
+
+        # The start of a segment isn't a cut, only the changes detected within it are
+        frame_numbers += segment_frame_numbers[1:]
+
+    return frame_numbers
+
+def detect_scene_changes_parallel(video_path, segments=4, overlap_frames=30, min_scene_len=15, include_start=True, **options):
+    # Every segment has its own end, so a frame limit has no single meaning here
+    if options.get('max_frames'):
+        raise ValueError("A frame limit can't be used with segment-parallel detection, use end_time on detect_scene_changes instead.")
+
+    validate_video(video_path)
+    total_frames = get_video_info(video_path)['frame_count']
+    segment_length = -(-total_frames // segments)
+
+    # Each segment starts a little early so the detector is warmed up when it
+    # reaches the part of the video the segment is responsible for
+    def detect_segment(segment_index):
+        own_start = segment_index * segment_length
+        own_end = min(own_start + segment_length, total_frames)
+        start = max(own_start - overlap_frames, 0)
+        is_last_segment = segment_index == segments - 1
+        end = None if is_last_segment else own_end
+        frame_numbers = detect_scene_changes(video_path, start_time=start, end_time=end, min_scene_len=min_scene_len, **options)
+
+        # Keep only real cuts in the segment's own part, the segment start isn't one. The
+        # last segment runs to the end of the video, which may be past the estimated frame count.
+        return [frame for frame in frame_numbers if own_start <= frame and (is_last_segment or frame < own_end) and frame != start]
+
+    # OpenCV releases the GIL while decoding, so threads run the segments in parallel
+    with ThreadPoolExecutor(max_workers=segments) as executor:
+        segment_frame_numbers = list(executor.map(detect_segment, range(segments)))
+
+    # Drop duplicates of the same cut found by two segments near a seam
+    cuts = sorted(frame for segment_cuts in segment_frame_numbers for frame in segment_cuts)
+    frame_numbers = [0] if include_start else []
+    for frame in cuts:
+        if not frame_numbers or frame - frame_numbers[-1] >= min_scene_len:
+            frame_numbers.append(frame)
+
+    return frame_numbers
+
+def detect_multiple(video_paths, **options):
+    # Detect every video with the same settings, one failing video doesn't stop the rest
+    results = {}
+    for video_path in video_paths:
+        try:
+            results[video_path] = detect_with_report(video_path, **options)
+        except Exception as error:
+            logging.error(f"Scene detection failed for '{video_path}': {error}")
+            results[video_path] = error
+
+    return results
+
+def compute_frame_metrics(video_path, downscale=None):
+    video = open_video(video_path)
+    stats_manager = StatsManager()
+    scene_manager = SceneManager(stats_manager)
+    scene_manager.add_detector(MarkItContentDetector())
+
+    # Use a fixed downscale factor instead of the automatic one if requested
+    if downscale:
+        scene_manager.auto_downscale = False
+        scene_manager.downscale = downscale
+
+    # Run a scoring pass, the cuts themselves are ignored
+    num_frames = scene_manager.detect_scenes(video)
+
+    return get_frame_metrics(stats_manager, num_frames)
+
+def compute_frame_scores(video_path, downscale=None):
+    return [frame_metrics.content_val for frame_metrics in compute_frame_metrics(video_path, downscale)]
+
+def suggest_threshold(video_path, downscale=None):
+    # Recommend a threshold from the distribution of frame scores
+    scores = compute_frame_scores(video_path, downscale)
+    return suggest_threshold_from_scores(scores)
+
+def analyze_video(video_path, thresholds=(15, 20, 25, 27, 30, 35, 40), min_scene_len=15, downscale=None):
+    # Score statistics and predicted cut counts, without settling on a threshold
+    scores = compute_frame_scores(video_path, downscale)
+    return analyze_frame_scores(scores, thresholds, min_scene_len)
+
+def detect_scene_changes_at_thresholds(video_path, thresholds, min_scene_len=15, downscale=None):
+    # Score the video once and apply every threshold to the same scores
+    scores = compute_frame_scores(video_path, downscale)
+    return sweep_thresholds(scores, thresholds, min_scene_len)
+
+def detect_n_scenes(video_path, target_count, tolerance=1, min_scene_len=15, downscale=None):
+    # Find the threshold giving about the requested number of scenes, e.g. "about 12 chapters",
+    # scoring the video once and returning (threshold, frame numbers)
+    scores = compute_frame_scores(video_path, downscale)
+    return threshold_for_scene_count(scores, target_count, tolerance, min_scene_len)
+
+def compute_cut_probabilities(video_path, threshold=27.0, softness=None, downscale=None):
+    # Per-frame cut likelihoods instead of cuts, 0.5 at the threshold
+    scores = compute_frame_scores(video_path, downscale)
+    return cut_probabilities(scores, threshold, softness)
+
+def get_frame_scores(stats_manager, num_frames, metric_key='content_val', start_frame=0):
+    # Flatten the collected metrics into a list of scores indexed by frame number,
+    # with frames before the start of detection scoring 0
+    scores = [0.0] * start_frame
+    for frame_number in range(start_frame, start_frame + num_frames):
+        score = stats_manager.get_metrics(frame_number, [metric_key])[0]
+        scores.append(score or 0.0)
+
+    return scores
+
+def get_frame_metrics(stats_manager, num_frames):
+    # Per-channel deltas and the blended score of every frame
+    frame_metrics = []
+    for frame_number in range(num_frames):
+        values = stats_manager.get_metrics(frame_number, FrameMetrics._fields[1:])
+        frame_metrics.append(FrameMetrics(frame_number, *(value or 0.0 for value in values)))
+
+    return frame_metrics
+
+def score_frame_pair(previous_frame, current_frame, **scoring_options):
+    # Score the pair the same way MarkItContentDetector scores consecutive frames, on a
+    # fresh detector so no state carries over from other frames
+    content_detector = MarkItContentDetector(**scoring_options)
+    content_detector.process_frame(0, previous_frame)
+    content_detector.process_frame(1, current_frame)
+
+    return content_detector.last_frame_score
+
+def frames_match_in_video(video_capture, before_frame_number, after_frame_number, threshold=27.0, **scoring_options):
+    # The picture changed back if the frames differ by less than a cut would
+    before_frame = read_frame(video_capture, before_frame_number)
+    after_frame = read_frame(video_capture, after_frame_number)
+
+    # Keep the cut if the frames can't be read back, rather than losing it
+    if before_frame is None or after_frame is None:
+        return False
+
+    return score_frame_pair(before_frame, after_frame, threshold=threshold, **scoring_options) < threshold
+
+def confirm_scene_changes(video_path, frame_numbers, threshold=27.0, **scoring_options):
+    # Scoring options are MarkItContentDetector's, e.g. weights and ignored regions
+    video_capture = cv2.VideoCapture(video_path)
+
+    confirmed_frame_numbers = []
+    for frame_number in frame_numbers:
+        # The first scene always starts at the beginning of the video
+        if frame_number == 0:
+            confirmed_frame_numbers.append(frame_number)
+            continue
+
+        previous_frame, current_frame = read_frame_pair(video_capture, frame_number)
+
+        # Keep the candidate if the frames can't be read back, rather than losing a cut
+        if previous_frame is None:
+            confirmed_frame_numbers.append(frame_number)
+            continue
+
+        if score_frame_pair(previous_frame, current_frame, threshold=threshold, **scoring_options) >= threshold:
+            confirmed_frame_numbers.append(frame_number)
+
+    video_capture.release()
+
+    return confirmed_frame_numbers
@@ -0,0 +1,50 @@
+import json
+
+import cv2
+
+def calculate_hsv_histogram(frame, bins=(8, 4, 4)):
+    # Compact (hue, saturation, value) histogram, normalized to sum to 1
+    hsv_frame = cv2.cvtColor(frame, cv2.COLOR_BGR2HSV)
+    histogram = cv2.calcHist([hsv_frame], [0, 1, 2], None, list(bins), [0, 180, 0, 256, 0, 256]).flatten()
+
+    return histogram / max(histogram.sum(), 1)
+
+def get_scene_histograms(scene_histogram_sums, frame_numbers):
+    # Combine the (histogram sum, frame count) collected since each detector cut into
+    # the final scenes, which filtering may have merged
+    segment_starts = sorted(scene_histogram_sums)
+    scene_starts = sorted(frame_numbers)
+    scene_histograms = []
+    segment_index = 0
+    for i, start in enumerate(scene_starts):
+        end = scene_starts[i + 1] if i + 1 < len(scene_starts) else None
+
+        # Find the segments that start within the scene
+        while segment_index < len(segment_starts) and segment_starts[segment_index] < start:
+            segment_index += 1
+        first_segment_index = segment_index
+        while segment_index < len(segment_starts) and (end is None or segment_starts[segment_index] < end):
+            segment_index += 1
+        scene_segment_starts = segment_starts[first_segment_index:segment_index]
+
+        # A scene no detector cut starts in, e.g. one found by another detector,
+        # gets the colors of the segment it is in
+        if not scene_segment_starts and first_segment_index > 0:
+            scene_segment_starts = [segment_starts[first_segment_index - 1]]
+
+        segment_sums = [scene_histogram_sums[segment_start] for segment_start in scene_segment_starts]
+        frame_count = sum(segment_count for _, segment_count in segment_sums)
+        if frame_count:
+            scene_histograms.append((start, sum(segment_sum for segment_sum, _ in segment_sums) / frame_count))
+
+    return scene_histograms
+
+def export_scene_histograms(json_file_path, scene_histograms, bins=(8, 4, 4)):
+    # One entry per scene, with the histogram flattened in hue, saturation, value order
+    data = {
+        'bins': list(bins),
+        'scenes': [{'start': start, 'histogram': [round(float(value), 6) for value in histogram]}
+                   for start, histogram in scene_histograms],
+    }
+    with open(json_file_path, 'w') as json_file:
+        json.dump(data, json_file)
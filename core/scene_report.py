@@ -0,0 +1,48 @@
+import statistics
+
+# Upper bounds in seconds of the scene duration histogram buckets
+DURATION_BUCKETS = [1, 2, 5, 10, 30, 60, float('inf')]
+
+def scene_length_report(frame_numbers, total_frames, fps):
+    # Each scene lasts until the next one starts, the last one until the end of the video
+    scene_ends = frame_numbers[1:] + [total_frames]
+    scene_lengths = [end - start for start, end in zip(frame_numbers, scene_ends)]
+    if not scene_lengths:
+        return None
+
+    # Count scenes per duration bucket
+    histogram = {bucket: 0 for bucket in DURATION_BUCKETS}
+    for length in scene_lengths:
+        duration = length / fps
+        bucket = next(bucket for bucket in DURATION_BUCKETS if duration < bucket)
+        histogram[bucket] += 1
+
+    return {
+        'scene_count': len(scene_lengths),
+        'min_length': min(scene_lengths),
+        'mean_length': statistics.mean(scene_lengths),
+        'median_length': statistics.median(scene_lengths),
+        'max_length': max(scene_lengths),
+        'duration_histogram': histogram,
+    }
+
+def check_cut_rate(frame_numbers, total_frames, fps, max_cuts_per_second=1.0, window_seconds=60, max_seconds_without_cuts=3600):
+    # A warning message when the cut rate is implausible for real content, which
+    # usually means the threshold doesn't suit the video, None otherwise
+    cuts = [frame_number for frame_number in frame_numbers if frame_number != 0]
+    duration = total_frames / fps
+    if not cuts and duration >= max_seconds_without_cuts:
+        return f"No cuts detected in {duration / 60:.0f} minutes of video, the threshold may be too high."
+
+    # Look for a window with too many cuts, a short burst of fast cutting is fine
+    window_frames = round(window_seconds * fps)
+    max_cuts = max_cuts_per_second * window_seconds
+    window_start = 0
+    for window_end, cut in enumerate(cuts):
+        while cut - cuts[window_start] >= window_frames:
+            window_start += 1
+        if window_end - window_start + 1 > max_cuts:
+            return (f"More than {max_cuts_per_second:g} cuts per second for {window_seconds} seconds from frame "
+                    f"{cuts[window_start]}, the threshold may be too low.")
+
+    return None
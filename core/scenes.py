@@ -0,0 +1,37 @@
+from dataclasses import dataclass, field
+from typing import Optional
+
+@dataclass
+class Scene:
+    index: int
+    start: int  # First frame of the scene
+    end: int  # First frame after the scene
+    duration: float  # Seconds
+    score_stats: Optional[dict] = None
+    label: Optional[str] = None  # Used as the marker text when exporting
+    tags: list = field(default_factory=list)
+    notes: str = ''
+
+def get_scene_list(frame_numbers, total_frames, fps, metrics=None, include_opening_scene=True):
+    # The content before the first scene change is a scene of its own
+    if include_opening_scene and (not frame_numbers or frame_numbers[0] != 0):
+        frame_numbers = [0] + frame_numbers
+    elif not include_opening_scene and frame_numbers and frame_numbers[0] == 0:
+        frame_numbers = frame_numbers[1:]
+
+    # Each scene lasts until the next one starts, the last one until the end of the video
+    scene_ends = frame_numbers[1:] + [total_frames]
+
+    scenes = []
+    for i, (start, end) in enumerate(zip(frame_numbers, scene_ends)):
+        scene = Scene(index=i, start=start, end=end, duration=(end - start) / fps)
+
+        # Summarize the frame scores within the scene if metrics are available
+        if metrics:
+            scores = [metrics[frame]['content_val'] for frame in range(start, end) if 'content_val' in metrics.get(frame, {})]
+            if scores:
+                scene.score_stats = {'mean': sum(scores) / len(scores), 'max': max(scores)}
+
+        scenes.append(scene)
+
+    return scenes
@@ -0,0 +1,37 @@
+import time
+
+from scenedetect import SceneDetector
+
+from core.frame_difference import calculate_ssim
+
+class SsimDetector(SceneDetector):
+    def __init__(self, threshold=0.5, min_scene_len=15):
+        super().__init__()
+        self.threshold = threshold  # Minimum dissimilarity (1 - SSIM) for a cut
+        self.min_scene_len = min_scene_len
+        self.previous_frame_img = None
+        self.last_cut = None
+        self.processing_time = 0.0  # Total seconds spent scoring frames
+
+    def process_frame(self, frame_num, frame_img):
+        processing_started_at = time.monotonic()
+        cuts = self.find_cuts(frame_num, frame_img)
+        self.processing_time += time.monotonic() - processing_started_at
+
+        return cuts
+
+    def find_cuts(self, frame_num, frame_img):
+        previous_frame_img = self.previous_frame_img
+        self.previous_frame_img = frame_img
+        if self.last_cut is None:
+            self.last_cut = frame_num
+        if previous_frame_img is None:
+            return []
+
+        # Structural changes mark a cut, brightness flicker barely affects SSIM
+        dissimilarity = 1 - calculate_ssim(previous_frame_img, frame_img)
+        if dissimilarity >= self.threshold and frame_num - self.last_cut >= self.min_scene_len:
+            self.last_cut = frame_num
+            return [frame_num]
+
+        return []
@@ -0,0 +1,59 @@
+import cv2
+
+def detect_templates(video_path, template_paths, threshold=0.8, frame_step=5, analysis_width=320):
+    # Open the video file
+    video_capture = cv2.VideoCapture(video_path)
+    frame_width = video_capture.get(cv2.CAP_PROP_FRAME_WIDTH)
+    scale = analysis_width / frame_width if frame_width > analysis_width else 1.0
+
+    # Reference images are captured at the video resolution, scale them like the frames
+    templates = {}
+    for label, template_path in template_paths.items():
+        template = cv2.imread(template_path, cv2.IMREAD_GRAYSCALE)
+        if template is None:
+            raise FileNotFoundError(f"Template image '{template_path}' could not be read.")
+        templates[label] = cv2.resize(template, None, fx=scale, fy=scale, interpolation=cv2.INTER_AREA)
+
+    # Check every Nth frame for each template
+    matched_frames = {label: [] for label in templates}
+    frame_number = 0
+    while True:
+        if frame_number % frame_step != 0:
+            if not video_capture.grab():
+                break
+            frame_number += 1
+            continue
+
+        success, frame = video_capture.read()
+        if not success:
+            break
+        gray = cv2.resize(cv2.cvtColor(frame, cv2.COLOR_BGR2GRAY), None, fx=scale, fy=scale, interpolation=cv2.INTER_AREA)
+
+        for label, template in templates.items():
+            if template.shape[0] > gray.shape[0] or template.shape[1] > gray.shape[1]:
+                continue
+            if cv2.minMaxLoc(cv2.matchTemplate(gray, template, cv2.TM_CCOEFF_NORMED))[1] >= threshold:
+                matched_frames[label].append(frame_number)
+
+        frame_number += 1
+
+    # Release the video capture object
+    video_capture.release()
+
+    # Report each run of matches as one labeled (label, start, end) event
+    events = []
+    for label, frames in matched_frames.items():
+        events += [(label, start, end) for start, end in group_matched_frames(frames, frame_step)]
+
+    return sorted(events, key=lambda event: event[1])
+
+def group_matched_frames(matched_frames, frame_step):
+    # Join matches on consecutive sampled frames into (start, end) ranges, end exclusive
+    ranges = []
+    for frame in matched_frames:
+        if ranges and frame - ranges[-1][1] < frame_step:
+            ranges[-1][1] = frame + 1
+        else:
+            ranges.append([frame, frame + 1])
+
+    return [tuple(frame_range) for frame_range in ranges]
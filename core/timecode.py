@@ -0,0 +1,16 @@
+def format_timecode(frame_number, fps):
+    # Convert a frame number to HH:MM:SS.mmm
+    total_milliseconds = round(frame_number / fps * 1000)
+    hours, remainder = divmod(total_milliseconds, 3600 * 1000)
+    minutes, remainder = divmod(remainder, 60 * 1000)
+    seconds, milliseconds = divmod(remainder, 1000)
+
+    return f"{hours:02d}:{minutes:02d}:{seconds:02d}.{milliseconds:03d}"
+
+def parse_timecode(timecode, fps):
+    # Convert HH:MM:SS.mmm (or MM:SS.mmm, or seconds) to the nearest frame number
+    seconds = 0.0
+    for part in timecode.split(':'):
+        seconds = seconds * 60 + float(part)
+
+    return round(seconds * fps)
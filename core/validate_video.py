@@ -0,0 +1,46 @@
+import os
+
+import cv2
+
+class InvalidVideoError(Exception):
+    pass
+
+class NoVideoStreamError(InvalidVideoError):
+    pass
+
+class StillImageError(InvalidVideoError):
+    pass
+
+def validate_video(video_path):
+    if not os.path.isfile(video_path):
+        raise FileNotFoundError(f"Video file '{video_path}' does not exist.")
+
+    # Open the video file
+    video_capture = cv2.VideoCapture(video_path)
+    opened = video_capture.isOpened()
+    frame_count = int(video_capture.get(cv2.CAP_PROP_FRAME_COUNT)) if opened else 0
+
+    # Release the video capture object
+    video_capture.release()
+
+    # OpenCV can't open files without a video stream, and can't tell why, so only
+    # report an audio file when its header shows it is one
+    if not opened:
+        if is_audio_file(video_path):
+            raise NoVideoStreamError(f"'{video_path}' is an audio file with no video stream. Audio-only files can't be used for scene detection.")
+        raise InvalidVideoError(f"'{video_path}' can't be read as a video. It may be audio-only, corrupt or in an unsupported format.")
+
+    # A single frame means the file is an image rather than a video
+    if frame_count == 1:
+        raise StillImageError(f"'{video_path}' is a still image. Scene detection needs a video file.")
+
+def is_audio_file(file_path):
+    # Recognize common audio formats by their first bytes
+    with open(file_path, 'rb') as file:
+        header = file.read(12)
+
+    return (header.startswith((b'ID3', b'fLaC'))
+            or (header[:4] == b'RIFF' and header[8:12] == b'WAVE')
+            or (header[4:8] == b'ftyp' and header[8:11] in (b'M4A', b'M4B'))
+            # MP3 frames and ADTS AAC start with a frame sync
+            or (len(header) >= 2 and header[0] == 0xFF and header[1] & 0xE0 == 0xE0))
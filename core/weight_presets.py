@@ -0,0 +1,22 @@
+from scenedetect import ContentDetector
+
+# Named (hue, saturation, luminance, edges) weights for the content score
+WEIGHT_PRESETS = {
+    'default': ContentDetector.Components(delta_hue=1.0, delta_sat=1.0, delta_lum=1.0, delta_edges=0.0),
+    'luma_only': ContentDetector.Components(delta_hue=0.0, delta_sat=0.0, delta_lum=1.0, delta_edges=0.0),
+    'chroma_only': ContentDetector.Components(delta_hue=1.0, delta_sat=1.0, delta_lum=0.0, delta_edges=0.0),
+    'edges_heavy': ContentDetector.Components(delta_hue=1.0, delta_sat=1.0, delta_lum=1.0, delta_edges=2.0),
+}
+
+def register_weight_preset(name, weights):
+    # Add a preset, or replace an existing one
+    WEIGHT_PRESETS[name] = ContentDetector.Components(*weights)
+
+def resolve_weights(weights):
+    # Accept a preset name or the four weights themselves
+    if isinstance(weights, str):
+        if weights not in WEIGHT_PRESETS:
+            raise ValueError(f"Unknown weight preset '{weights}', available presets are: {', '.join(WEIGHT_PRESETS)}.")
+        return WEIGHT_PRESETS[weights]
+
+    return ContentDetector.Components(*weights)
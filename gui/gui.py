@@ -1,9 +1,14 @@
 import sys
 sys.path.append('../')
 
-from core.scene_detection import detect_scene_changes
+from core.scene_detection import detect_with_report
 from core.create_midi_with_markers import create_midi_with_markers
+from core.format_file_name import format_file_name
 from core.get_video_fps import get_video_fps
+from core.get_video_info import get_video_info
+from core.scenes import get_scene_list
+from core.timecode import format_timecode
+from core.validate_video import InvalidVideoError
 
 import tkinter as tk
 from tkinter import filedialog, messagebox
@@ -11,6 +16,7 @@ from tkinter import ttk
 from ttkbootstrap import Style
 
 import os
+import time
 import logging
 import threading
 
@@ -28,7 +34,7 @@ class TextHandler(logging.Handler):
         window.update_idletasks()  # Update the GUI
 
 def select_video():
-    video_path = filedialog.askopenfilename(filetypes=[("Video files", "*.mp4;*.avi;*.mov")])
+    video_path = filedialog.askopenfilename(filetypes=[("Video files", "*.mp4;*.avi;*.mov;*.gif")])
     if video_path:
         video_path_entry.delete(0, tk.END)
         video_path_entry.insert(0, video_path)
@@ -47,7 +53,24 @@ def setup_logging():
     handler.setFormatter(formatter)
     logger.addHandler(handler)
 
+def log_progress(progress):
+    global last_logged_progress_step
+
+    # Only log every 10% to keep the log readable
+    progress_step = int(progress.percent // 10)
+    if progress_step <= last_logged_progress_step:
+        return
+    last_logged_progress_step = progress_step
+
+    if progress.eta_seconds is None:
+        logging.info(f"Progress: {progress.percent:.0f}%")
+    else:
+        eta = time.strftime('%H:%M:%S', time.gmtime(progress.eta_seconds))
+        logging.info(f"Progress: {progress.percent:.0f}% ({progress.fps:.1f} FPS, {eta} remaining)")
+
 def process_video():
+    global last_logged_progress_step
+
     # Clear the log text widget
     log_text.config(state=tk.NORMAL)
     log_text.delete(1.0, tk.END)
@@ -57,22 +80,54 @@ def process_video():
     video_path = video_path_entry.get()
     output_path = output_path_entry.get()
     midi_file_name = midi_file_name_entry.get()
+    framerate_text = framerate_entry.get().strip()
     
     if not video_path or not output_path or not midi_file_name:
         messagebox.showerror("Error", "Please select video file, output path, and enter MIDI file name.")
         enable_buttons()
         return
+
+    # An optional frame rate overrides the one in the video metadata
+    framerate = None
+    if framerate_text:
+        try:
+            framerate = float(framerate_text)
+        except ValueError:
+            framerate = 0
+        if framerate <= 0:
+            messagebox.showerror("Error", "Frame rate must be a positive number, or left empty to use the video's frame rate.")
+            enable_buttons()
+            return
     
     logging.info(f"Detecting scene changes in '{video_path}'...")
     logging.info("This may take a few minutes depending on the video duration.")
-    frame_numbers = detect_scene_changes(video_path)
-    logging.info(f"Finished detecting {len(frame_numbers)} scenes in '{video_path}'.")
-
-    video_fps = get_video_fps(video_path)
-    logging.info(f"Video frame rate is {video_fps} FPS.")
-
-    output_file = os.path.join(output_path, midi_file_name + ".mid")
+    last_logged_progress_step = 0
+    try:
+        frame_numbers, report = detect_with_report(video_path, progress_callback=log_progress, framerate=framerate)
+    except (FileNotFoundError, InvalidVideoError) as error:
+        logging.error(str(error))
+        messagebox.showerror("Error", str(error))
+        enable_buttons()
+        return
+    logging.info(f"Finished detecting {len(frame_numbers)} scenes in '{video_path}' in {report.elapsed_time:.1f} seconds ({report.average_fps:.1f} FPS).")
+    logging.info("Time spent: " + ", ".join(f"{stage} {seconds:.1f} s" for stage, seconds in report.timings.items()))
+
+    if framerate:
+        video_fps = framerate
+        logging.info(f"Using frame rate override of {video_fps} FPS.")
+    else:
+        video_fps = get_video_fps(video_path)
+        logging.info(f"Video frame rate is {video_fps} FPS.")
+
+    # List every scene with its start, end and duration
+    total_frames = get_video_info(video_path)['frame_count']
+    for scene in get_scene_list(frame_numbers, total_frames, video_fps):
+        logging.info(f"Scene {scene.index + 1}: {format_timecode(scene.start, video_fps)} - {format_timecode(scene.end, video_fps)} ({scene.duration:.3f} s)")
+
+    # The MIDI file name may use placeholders like $VIDEO_NAME to tell batch outputs apart
+    output_file = os.path.join(output_path, format_file_name(midi_file_name, video_path) + ".mid")
     logging.info(f"Creating MIDI file with markers for '{video_path}'...")
+    os.makedirs(output_path, exist_ok=True)
     create_midi_with_markers(frame_numbers, output_file, fps=video_fps)
     logging.info(f"MIDI file saved to '{output_file}'")
     logging.info("Success: MIDI file saved.")
@@ -93,6 +148,7 @@ def disable_buttons():
     video_path_entry.config(state=tk.DISABLED)
     output_path_entry.config(state=tk.DISABLED)
     midi_file_name_entry.config(state=tk.DISABLED)
+    framerate_entry.config(state=tk.DISABLED)
 
 def enable_buttons():
     browse_video_button.config(state=tk.NORMAL)
@@ -101,6 +157,9 @@ def enable_buttons():
     video_path_entry.config(state=tk.NORMAL)
     output_path_entry.config(state=tk.NORMAL)
     midi_file_name_entry.config(state=tk.NORMAL)
+    framerate_entry.config(state=tk.NORMAL)
+
+last_logged_progress_step = 0
 
 style = Style(theme='darkly')
 window = style.master
@@ -126,20 +185,25 @@ ttk.Label(window, text="MIDI File Name (without .mid extension):").grid(row=2, c
 midi_file_name_entry = ttk.Entry(window, width=50, style='info.TEntry')
 midi_file_name_entry.grid(row=2, column=1, padx=10, pady=10)
 
+# Frame rate override input
+ttk.Label(window, text="Frame Rate (optional, overrides video metadata):").grid(row=3, column=0, padx=10, pady=10)
+framerate_entry = ttk.Entry(window, width=50, style='info.TEntry')
+framerate_entry.grid(row=3, column=1, padx=10, pady=10)
+
 # Process button
 process_button = ttk.Button(window, text="Process", command=process_video_thread)
-process_button.grid(row=3, column=0, columnspan=3, pady=20)
+process_button.grid(row=4, column=0, columnspan=3, pady=20)
 
 # Log label
-ttk.Label(window, text="Logs:").grid(row=4, column=0, padx=10, pady=10, sticky='w')
+ttk.Label(window, text="Logs:").grid(row=5, column=0, padx=10, pady=10, sticky='w')
 
 # Log text widget
 log_text = tk.Text(window, height=10, width=200, state=tk.DISABLED)
-log_text.grid(row=5, column=0, columnspan=3, padx=10, pady=10)
+log_text.grid(row=6, column=0, columnspan=3, padx=10, pady=10)
 
 # Version number
 version_number = "MarkIt Version 0.6"
-ttk.Label(window, text=version_number, style='info.TLabel').grid(row=6, column=2, padx=10, pady=10, sticky='e')
+ttk.Label(window, text=version_number, style='info.TLabel').grid(row=7, column=2, padx=10, pady=10, sticky='e')
 
 # Setup logging
 setup_logging()
@@ -1,10 +1,42 @@
+import json
 import os
+import sqlite3
 
+import cv2
 import mido
+import numpy as np
+import pytest
 from mido import MidiFile
-from core.scene_detection import detect_scene_changes
+from scenedetect import open_video
+from core import scene_detection
+from core.scene_detection import detect_scene_changes, detect_with_report, detect_stream, detect_multiple, detect_scene_changes_excluding, detect_scene_changes_parallel, score_frame_pair
 from core.create_midi_with_markers import create_midi_with_markers
-from core.get_video_fps import get_video_fps
+from core.get_video_fps import get_video_fps, is_valid_fps
+from core.progress import ProgressTracker
+from core.frame_scores import load_stats_file, suppress_flashes, suggest_threshold_from_scores, analyze_frame_scores, get_scene_change_metrics, scores_from_metrics, scene_changes_from_scores, FrameMetrics, frame_metrics_from_stats, load_scores_file, sweep_thresholds, smooth_scores, cut_probabilities, threshold_for_scene_count
+from core.match_scene_changes import match_scene_changes
+from core.content_detector import MarkItContentDetector
+from core.black_frame_detection import find_black_runs
+from core.scene_report import scene_length_report, check_cut_rate
+from core.get_video_info import get_video_info
+from core.validate_video import InvalidVideoError, NoVideoStreamError, StillImageError, is_audio_file, validate_video
+from core.get_gif_fps import get_gif_fps
+from core.timecode import format_timecode, parse_timecode
+from core.scenes import get_scene_list
+from core.frame_difference import calculate_grid_score, calculate_ssim, calculate_content_score, drop_alpha
+from core.export_to_sqlite import export_to_sqlite, load_scenes_from_sqlite
+from core.merge_scene_changes import load_extra_cuts, merge_scene_changes
+from core.template_detection import group_matched_frames
+from core.export_heatmaps import export_difference_heatmaps
+from core.weight_presets import register_weight_preset, resolve_weights
+from core.format_file_name import format_file_name
+from core.detection_presets import resolve_preset
+from core.detection_observer import DetectionObserver
+from core.detection_report import DetectionReport
+from core.scene_histograms import calculate_hsv_histogram, get_scene_histograms
+from core.grab_scene_frame import grab_scene_frame
+from core.export_playlist import export_to_mpv_edl, export_to_xspf
+from core.get_capabilities import get_capabilities
 
 def test_get_video_fps():
     # Path to the test video file
@@ -65,4 +97,915 @@ def test_create_midi(tmpdir):
     # Verify the marker times
     marker_times = [msg.time for msg in track if msg.type == 'marker']
     expected_marker_times = [0, 11520, 8947, 22579, 25229]
-    assert marker_times == expected_marker_times
\ No newline at end of file
+    assert marker_times == expected_marker_times
+
+def test_scene_detection_confirm_full_resolution():
+    # Path to the test video file
+    test_video_path = 'test_files/bele_rade_1080.mp4'
+
+    # Detect candidates on heavily downscaled frames, with and without the full resolution check
+    candidate_frame_numbers = detect_scene_changes(test_video_path, downscale=8)
+    confirmed_frame_numbers = detect_scene_changes(test_video_path, downscale=8, confirm_full_resolution=True)
+
+    # The clean cuts in the test video all hold up at full resolution
+    assert candidate_frame_numbers == [0, 300, 533, 1121, 1778]
+    assert confirmed_frame_numbers == [0, 300, 533, 1121, 1778]
+
+def test_score_frame_pair_uses_detector_settings():
+    # Red and blue frames differ only in hue, apart from a black box in the corner of the second
+    red_frame = np.zeros((90, 160, 3), np.uint8)
+    red_frame[:, :] = (0, 0, 255)
+    blue_frame = np.zeros((90, 160, 3), np.uint8)
+    blue_frame[:, :] = (255, 0, 0)
+    blue_frame[:10, :10] = 0
+
+    # Candidates are re-checked on the detector's metric, not the plain HSV mean
+    hue_score = score_frame_pair(red_frame, blue_frame, weights=resolve_weights('default'))
+    luma_score = score_frame_pair(red_frame, blue_frame, weights=resolve_weights('luma_only'))
+    masked_luma_score = score_frame_pair(red_frame, blue_frame, weights=resolve_weights('luma_only'),
+                                         ignore_regions=[(0, 0, 10, 10)], frame_width=160)
+    assert hue_score > 27.0
+    assert 0 < luma_score < 27.0
+    assert masked_luma_score == 0
+
+def test_progress_tracker():
+    # Collect every progress report for a 100 frame video, processed at 10 FPS on a fake clock
+    reports = []
+    clock_time = [0.0]
+    tracker = ProgressTracker(100, reports.append, report_every=30, clock=lambda: clock_time[0])
+    for _ in range(100):
+        clock_time[0] += 0.1
+        tracker.update()
+
+    # Reports come every 30 frames, plus a final one when processing completes
+    assert [report.frames_processed for report in reports] == [30, 60, 90, 100]
+    assert reports[-1].percent == 100.0 and reports[-1].eta_seconds == 0
+    assert [round(report.fps, 6) for report in reports] == [10.0] * 4
+    assert round(reports[0].eta_seconds, 6) == 7.0
+
+    # Stage rates are averages over the whole run so far
+    reports = []
+    tracker = ProgressTracker(100, reports.append, report_every=50, stage_times=lambda: (0.5, 2.0))
+    tracker.update(50)
+    assert reports[0].decode_fps == 100.0 and reports[0].analyze_fps == 25.0
+
+def test_load_stats_file(tmpdir):
+    # Write a stats file the way PySceneDetect's StatsManager does
+    stats_file_path = os.path.join(tmpdir, 'test_stats.csv')
+    with open(stats_file_path, 'w') as stats_file:
+        stats_file.write('Frame Number,Timecode,content_val,delta_hue,delta_sat,delta_lum\n')
+        stats_file.write('1,00:00:00.000,0.0,0.0,0.0,0.0\n')
+        stats_file.write('2,00:00:00.040,1.5,0.5,2.0,2.0\n')
+
+    # Load the metrics back
+    metrics = load_stats_file(stats_file_path)
+
+    # Verify frame numbers are 0-based and every metric column is read
+    assert sorted(metrics) == [0, 1]
+    assert metrics[1] == {'content_val': 1.5, 'delta_hue': 0.5, 'delta_sat': 2.0, 'delta_lum': 2.0}
+
+def test_scene_detection_with_blur():
+    # Path to the test video file
+    test_video_path = 'test_files/bele_rade_1080.mp4'
+
+    # Light blur shouldn't change the clean cuts in the test video
+    frame_numbers = detect_scene_changes(test_video_path, blur_kernel_size=5)
+
+    assert frame_numbers == [0, 300, 533, 1121, 1778]
+
+def test_suppress_flashes():
+    # Steady low scores with one real cut, and a strobe section where every frame scores high
+    scores = [2.0] * 40 + [45.0] + [2.0] * 19 + [40.0, 35.0, 42.0, 38.0, 36.0, 50.0, 39.0, 41.0] + [2.0] * 12
+    frame_numbers = [0, 40, 62, 65]
+
+    # Only the cut that isn't followed by more high scores survives
+    assert suppress_flashes(frame_numbers, scores, threshold=27.0, window=3) == [0, 40]
+
+def test_suppress_single_frame_flash():
+    # A camera flash: one frame lights up and the next one changes back
+    flash_scores = [3.0] * 100
+    flash_scores[40], flash_scores[41] = 60.0, 58.0
+
+    # A real cut in a busy scene whose scores stay high but below the threshold
+    busy_scores = [15.0] * 100
+    busy_scores[50] = 35.0
+
+    # The flash is dropped, the real cut is kept
+    assert scene_changes_from_scores(flash_scores) == [0, 40]
+    assert scene_changes_from_scores(flash_scores, flash_window=3) == [0]
+    assert scene_changes_from_scores(busy_scores, flash_window=3) == [0, 50]
+
+def test_suppress_flashes_keeps_cut_followed_by_motion():
+    # A hard cut, then a single fast-motion frame two frames later
+    scores = [3.0] * 100
+    scores[40], scores[42] = 60.0, 30.0
+
+    # The picture doesn't change back, so the cut is kept
+    assert scene_changes_from_scores(scores, flash_window=3) == [0, 40]
+
+    # Compared frame by frame, only a picture matching the one before the spike is a flash
+    assert suppress_flashes([0, 40], scores, 27.0, 3, frames_match=lambda before_frame, after_frame: False) == [0, 40]
+    assert suppress_flashes([0, 40], scores, 27.0, 3, frames_match=lambda before_frame, after_frame: (before_frame, after_frame) == (39, 42)) == [0]
+
+def test_suggest_threshold_from_scores():
+    # Mostly low scores within scenes, with a few high scores at cuts
+    scores = [1.0, 2.5, 3.0, 1.5, 4.0, 2.0] * 50 + [38.0, 45.0, 52.0, 41.0]
+
+    threshold = suggest_threshold_from_scores(scores)
+
+    # The suggested threshold separates the cuts from the rest, well clear of both
+    assert 8.0 < threshold < 30.0
+
+    # The same with noisier content, where frames within scenes score up to 11
+    noisy_scores = [2.0, 5.0, 8.0, 11.0, 4.0, 7.0, 3.0, 9.0, 6.0, 10.0] * 100 + [40.0, 45.0, 55.0] * 3
+    assert 15.0 < suggest_threshold_from_scores(noisy_scores) < 40.0
+    assert suggest_threshold_from_scores([]) is None
+
+def test_analyze_frame_scores():
+    # 100 frames of low scores with cuts of different strength at frames 30 and 70
+    scores = [2.0] * 100
+    scores[30] = 24.0
+    scores[70] = 50.0
+
+    analysis = analyze_frame_scores(scores, thresholds=[20, 30, 60])
+
+    # Verify the score statistics
+    assert analysis['max'] == 50.0
+    assert analysis['percentiles'][50] == 2.0
+    assert analysis['mean'] == (98 * 2.0 + 24.0 + 50.0) / 100
+
+    # Verify the predicted cut counts per threshold
+    assert analysis['cut_counts'] == {20: 2, 30: 1, 60: 0}
+
+def test_match_scene_changes():
+    # The same cuts in a 25 FPS encode and a 50 FPS encode that starts 1 second later, missing one cut
+    frame_numbers_a = [0, 300, 533, 1121, 1778]
+    frame_numbers_b = [50, 650, 2292, 3606]
+
+    result = match_scene_changes(frame_numbers_a, 25, frame_numbers_b, 50)
+
+    # Verify the offset and the matched and missing boundaries
+    assert result['offset'] == 1.0
+    assert result['matched'] == [(0, 50), (300, 650), (1121, 2292), (1778, 3606)]
+    assert result['missing_in_b'] == [533]
+    assert result['missing_in_a'] == []
+
+def test_mask_ignore_regions():
+    # Ignore the top-left 4x2 corner of an 8 pixel wide video
+    detector = MarkItContentDetector(ignore_regions=[(0, 0, 4, 2)], frame_width=8)
+
+    # Mask a frame downscaled by a factor of 2
+    frame_img = np.full((4, 4, 3), 255, dtype=np.uint8)
+    masked_img = detector.mask_ignore_regions(frame_img)
+
+    # Only the scaled region is blacked out, and the input frame is left untouched
+    assert (masked_img[0:1, 0:2] == 0).all()
+    assert (masked_img[1:, :] == 255).all() and (masked_img[:, 2:] == 255).all()
+    assert (frame_img == 255).all()
+
+def test_find_black_runs():
+    # A short dip to black, a long black run, and black frames at the very end
+    frame_lumas = [120] * 10 + [5] * 3 + [110] * 10 + [2] * 12 + [90] * 5 + [10] * 10
+
+    black_runs = find_black_runs(frame_lumas, luma_threshold=16, min_run_length=10)
+
+    # The short dip is too short to count as a break
+    assert black_runs == [(23, 35), (40, 50)]
+
+def test_scene_length_report():
+    # Scenes from the test video, which is 2000 frames long at 25 FPS
+    frame_numbers = [0, 300, 533, 1121, 1778]
+
+    report = scene_length_report(frame_numbers, 2000, 25)
+
+    # Verify the scene length statistics, in frames
+    assert report['scene_count'] == 5
+    assert report['min_length'] == 222 and report['max_length'] == 657
+    assert report['median_length'] == 300 and report['mean_length'] == 400
+
+    # Verify the scenes are counted in the right duration buckets
+    assert report['duration_histogram'] == {1: 0, 2: 0, 5: 0, 10: 2, 30: 3, 60: 0, float('inf'): 0}
+
+def test_get_video_info():
+    # Path to the test video file
+    test_video_path = 'test_files/bele_rade_1080.mp4'
+
+    info = get_video_info(test_video_path)
+
+    # Verify the properties of the 1080p H.264 test video
+    assert info['width'] == 1920 and info['height'] == 1080
+    assert info['fps'] == 25
+    assert info['codec'] in ('avc1', 'h264')
+    assert info['rotation'] == 0
+
+def test_get_video_info_rotated(monkeypatch):
+    # A portrait phone video, which OpenCV already reports with its rotated dimensions
+    class RotatedVideoCapture:
+        def __init__(self, video_path):
+            self.properties = {cv2.CAP_PROP_FRAME_WIDTH: 1080, cv2.CAP_PROP_FRAME_HEIGHT: 1920,
+                               cv2.CAP_PROP_FPS: 30, cv2.CAP_PROP_ORIENTATION_META: 90}
+
+        def get(self, property_id):
+            return self.properties.get(property_id, 0)
+
+        def release(self):
+            pass
+
+    monkeypatch.setattr(cv2, 'VideoCapture', RotatedVideoCapture)
+    info = get_video_info('portrait.mp4')
+
+    # The dimensions are the ones of the frames as they are decoded, not swapped again
+    assert info['width'] == 1080 and info['height'] == 1920
+    assert info['rotation'] == 90
+
+def test_validate_video_without_video_stream(tmpdir):
+    # An MP3 podcast with a video file name, and a file that is just unreadable
+    podcast_path = os.path.join(tmpdir, 'podcast.mp4')
+    with open(podcast_path, 'wb') as podcast:
+        podcast.write(b'ID3\x04\x00\x00\x00\x00\x00\x00' + b'\x00' * 100)
+    not_a_video_path = os.path.join(tmpdir, 'corrupt.mp4')
+    with open(not_a_video_path, 'wb') as not_a_video:
+        not_a_video.write(b'not a video')
+    assert is_audio_file(podcast_path) and not is_audio_file(not_a_video_path)
+
+    # Verify a dedicated error is raised for the audio file only, and for missing files
+    with pytest.raises(NoVideoStreamError):
+        validate_video(podcast_path)
+    with pytest.raises(InvalidVideoError) as error:
+        validate_video(not_a_video_path)
+    assert not isinstance(error.value, NoVideoStreamError)
+    with pytest.raises(FileNotFoundError):
+        validate_video(os.path.join(tmpdir, 'missing.mp4'))
+
+def test_validate_video_still_image(tmpdir):
+    # A single frame saved as a PNG opens as a one-frame video
+    image_path = os.path.join(tmpdir, 'still.png')
+    cv2.imwrite(image_path, np.zeros((90, 160, 3), np.uint8))
+
+    with pytest.raises(StillImageError):
+        validate_video(image_path)
+
+def test_get_gif_fps(tmpdir, monkeypatch):
+    # A 1x1 GIF with three frames shown for 4, 4 and 12 hundredths of a second
+    frame = lambda delay: (b'\x21\xF9\x04\x00' + delay.to_bytes(2, 'little') + b'\x00\x00'
+                           + b'\x2C\x00\x00\x00\x00\x01\x00\x01\x00\x00' + b'\x02\x02\x44\x01\x00')
+    gif_data = (b'GIF89a\x01\x00\x01\x00\x80\x00\x00' + b'\x00\x00\x00\xFF\xFF\xFF'
+                + frame(4) + frame(4) + frame(12) + b'\x3B')
+    gif_path = os.path.join(tmpdir, 'test.gif')
+    with open(gif_path, 'wb') as gif_file:
+        gif_file.write(gif_data)
+
+    # Three frames over 0.2 seconds is 15 FPS
+    assert get_gif_fps(gif_path) == 15
+
+    # Detection opens the GIF at that frame rate rather than OpenCV's
+    opened_framerates = []
+    def open_video(video_path, framerate=None, **options):
+        opened_framerates.append(framerate)
+        raise RuntimeError("Stop before decoding")
+    monkeypatch.setattr(scene_detection, 'validate_video', lambda video_path: None)
+    monkeypatch.setattr(scene_detection, 'open_video', open_video)
+    with pytest.raises(RuntimeError):
+        detect_scene_changes(gif_path)
+    assert opened_framerates == [15]
+
+def test_detection_with_other_backend_skips_opencv_checks(tmpdir, monkeypatch):
+    # A file only PyAV can read, which OpenCV rejects
+    video_path = os.path.join(tmpdir, 'pyav_only.mkv')
+    with open(video_path, 'wb') as video_file:
+        video_file.write(b'\x1A\x45\xDF\xA3')
+    def reject_video(video_path):
+        raise InvalidVideoError("OpenCV can't read it")
+    monkeypatch.setattr(scene_detection, 'validate_video', reject_video)
+    monkeypatch.setattr(scene_detection, 'get_video_fps', lambda video_path: 0.0)
+
+    # The file goes straight to the chosen backend, with no frame rate override
+    opened_videos = []
+    def open_video(video_path, framerate=None, backend='opencv', **options):
+        opened_videos.append((framerate, backend, options))
+        raise RuntimeError("Stop before decoding")
+    monkeypatch.setattr(scene_detection, 'open_video', open_video)
+    with pytest.raises(RuntimeError):
+        detect_scene_changes(video_path, backend='pyav')
+    assert opened_videos == [(None, 'pyav', {})]
+
+def test_format_timecode():
+    # Verify frame numbers are converted to HH:MM:SS.mmm at the given frame rate
+    assert format_timecode(0, 25) == '00:00:00.000'
+    assert format_timecode(533, 25) == '00:00:21.320'
+    assert format_timecode(90000 + 1, 25) == '01:00:00.040'
+    assert format_timecode(1001, 30000 / 1001) == '00:00:33.400'
+
+def test_get_scene_change_metrics():
+    # Metrics as loaded from a stats file
+    metrics = {
+        299: {'content_val': 1.2, 'delta_hue': 0.4},
+        300: {'content_val': 48.5, 'delta_hue': 30.1},
+    }
+
+    scene_change_metrics = get_scene_change_metrics([0, 300], metrics)
+
+    # Frames without metrics only report their frame number
+    assert scene_change_metrics == [
+        {'frame_number': 0},
+        {'frame_number': 300, 'content_val': 48.5, 'delta_hue': 30.1},
+    ]
+
+def test_get_scene_list():
+    # Scene starts in a 100 frame video at 25 FPS, with scores for a few frames
+    frame_numbers = [0, 40, 75]
+    metrics = {10: {'content_val': 2.0}, 20: {'content_val': 4.0}, 40: {'content_val': 50.0}}
+
+    scenes = get_scene_list(frame_numbers, 100, 25, metrics)
+
+    # Verify scene boundaries, durations and score statistics
+    assert [(scene.index, scene.start, scene.end) for scene in scenes] == [(0, 0, 40), (1, 40, 75), (2, 75, 100)]
+    assert [scene.duration for scene in scenes] == [1.6, 1.4, 1.0]
+    assert scenes[0].score_stats == {'mean': 3.0, 'max': 4.0}
+    assert scenes[2].score_stats is None
+
+def test_get_scene_list_opening_scene():
+    # Scene changes that don't include the start of the video
+    frame_numbers = [40, 75]
+
+    # The opening scene is added by default, and can be left out
+    assert [scene.start for scene in get_scene_list(frame_numbers, 100, 25)] == [0, 40, 75]
+    assert [scene.start for scene in get_scene_list([0] + frame_numbers, 100, 25, include_opening_scene=False)] == [40, 75]
+    assert [(scene.start, scene.end) for scene in get_scene_list([], 100, 25)] == [(0, 100)]
+
+def test_scene_detection_without_start():
+    # Path to the test video file
+    test_video_path = 'test_files/bele_rade_1080.mp4'
+
+    # Only real cuts are reported when the start of the video is left out
+    frame_numbers = detect_scene_changes(test_video_path, include_start=False)
+
+    assert frame_numbers == [300, 533, 1121, 1778]
+
+def test_scene_changes_from_scores():
+    # Scores loaded from a stats file, with a cut at frame 30 and a weaker one at frame 35
+    metrics = {frame_number: {'content_val': 1.0} for frame_number in range(60)}
+    metrics[30] = {'content_val': 45.0}
+    metrics[35] = {'content_val': 30.0}
+    scores = scores_from_metrics(metrics)
+
+    # Re-run thresholding with different settings on the same scores
+    assert scene_changes_from_scores(scores) == [0, 30]
+    assert scene_changes_from_scores(scores, min_scene_len=5) == [0, 30, 35]
+    assert scene_changes_from_scores(scores, threshold=40.0, min_scene_len=5, include_start=False) == [30]
+
+def test_frame_metrics_from_stats():
+    # Metrics as loaded from a stats file written without edge detection
+    metrics = {0: {'content_val': 0.0, 'delta_hue': 0.0, 'delta_sat': 0.0, 'delta_lum': 0.0},
+               1: {'content_val': 1.5, 'delta_hue': 0.5, 'delta_sat': 2.0, 'delta_lum': 2.0}}
+
+    frame_metrics = frame_metrics_from_stats(metrics)
+
+    # Every frame gets all the components, missing edges default to 0
+    assert frame_metrics[1] == FrameMetrics(1, 1.5, 0.5, 2.0, 2.0, 0.0)
+    assert frame_metrics[1].delta_sat == 2.0
+
+def test_calculate_grid_score():
+    # A static gray frame where only one corner changes, and a frame where everything changes
+    previous_frame = np.full((80, 80, 3), 100, dtype=np.uint8)
+    local_change_frame = previous_frame.copy()
+    local_change_frame[0:20, 0:20] = 255
+    global_change_frame = np.full((80, 80, 3), 255, dtype=np.uint8)
+
+    # The localized change is trimmed away, the global one isn't
+    assert calculate_grid_score(previous_frame, local_change_frame, 4, 4) == 0
+    assert calculate_grid_score(previous_frame, global_change_frame, 4, 4) > 27
+
+def test_calculate_ssim():
+    # A textured frame, a brighter version of it, and an unrelated frame
+    rng = np.random.default_rng(0)
+    frame = rng.integers(0, 200, (64, 64, 3), dtype=np.uint8)
+    brighter_frame = frame + 30
+    other_frame = rng.integers(0, 200, (64, 64, 3), dtype=np.uint8)
+
+    # Identical and flickering frames stay similar, unrelated frames don't
+    assert calculate_ssim(frame, frame) == pytest.approx(1.0)
+    assert calculate_ssim(frame, brighter_frame) > 0.8
+    assert calculate_ssim(frame, other_frame) < 0.2
+
+def test_export_to_sqlite(tmpdir):
+    # Scenes and metrics of a 100 frame video
+    database_path = os.path.join(tmpdir, 'test_output.db')
+    scenes = get_scene_list([0, 40, 75], 100, 25)
+    frame_metrics = [FrameMetrics(0, 0.0, 0.0, 0.0, 0.0, 0.0), FrameMetrics(40, 48.5, 30.1, 50.2, 65.2, 0.0)]
+
+    # Exporting twice replaces the earlier results for the video
+    export_to_sqlite(database_path, 'video.mp4', scenes, frame_metrics)
+    export_to_sqlite(database_path, 'video.mp4', scenes, frame_metrics)
+
+    # Verify the exported tables
+    connection = sqlite3.connect(database_path)
+    assert connection.execute('SELECT scene_index, start_frame, end_frame, duration FROM scenes').fetchall() == [(0, 0, 40, 1.6), (1, 40, 75, 1.4), (2, 75, 100, 1.0)]
+    assert connection.execute('SELECT frame_number FROM cuts').fetchall() == [(40,), (75,)]
+    assert connection.execute('SELECT content_val FROM frame_metrics WHERE frame_number = 40').fetchall() == [(48.5,)]
+    connection.close()
+
+def test_load_scores_file(tmpdir):
+    # Scores computed by another tool, with a cut at frame 3 and frame 2 missing
+    scores_file_path = os.path.join(tmpdir, 'scores.csv')
+    with open(scores_file_path, 'w') as scores_file:
+        scores_file.write('frame,score\n0,0.0\n1,1.5\n3,42.0\n4,1.0\n')
+
+    scores = load_scores_file(scores_file_path)
+
+    # The scores can be thresholded like MarkIt's own
+    assert scores == [0.0, 1.5, 0.0, 42.0, 1.0]
+    assert scene_changes_from_scores(scores, min_scene_len=2) == [0, 3]
+
+def test_sweep_thresholds():
+    # Cuts of different strength at frames 30 and 70
+    scores = [2.0] * 100
+    scores[30] = 24.0
+    scores[70] = 50.0
+
+    # Verify the scene changes found at each threshold
+    assert sweep_thresholds(scores, [20, 30, 60]) == {20: [0, 30, 70], 30: [0, 70], 60: [0]}
+
+def test_detect_with_report():
+    # Path to the test video file
+    test_video_path = 'test_files/bele_rade_1080.mp4'
+
+    # Detect scenes in the whole video, and in the first 400 frames only
+    frame_numbers, report = detect_with_report(test_video_path)
+    limited_frame_numbers, limited_report = detect_with_report(test_video_path, max_frames=400)
+
+    # Verify the report of the full run
+    assert frame_numbers == [0, 300, 533, 1121, 1778]
+    assert report.frames_processed > 1778 and not report.truncated and report.warnings == []
+    assert report.configuration['threshold'] == 27.0 and report.average_fps > 0
+    assert report.decode_fps > 0 and report.analyze_fps > 0
+    assert report.backend == 'opencv'
+    assert set(report.timings) == {'decode', 'queue_wait', 'preprocess', 'score', 'ssim', 'callbacks', 'filter'}
+    assert report.timings['ssim'] == 0
+
+    # Verify the limited run is reported as truncated
+    assert limited_frame_numbers == [0, 300]
+    assert limited_report.truncated and limited_report.frames_processed == 400
+    assert len(limited_report.warnings) == 1
+
+def test_is_valid_fps():
+    # Verify common frame rates are accepted and broken metadata isn't
+    assert is_valid_fps(25) and is_valid_fps(30000 / 1001)
+    assert not is_valid_fps(0)
+    assert not is_valid_fps(float('nan'))
+    assert not is_valid_fps(1000)
+
+def test_detect_stream():
+    # Open the test video and seek past the first cut
+    video = open_video('test_files/bele_rade_1080.mp4')
+    video.seek(400)
+
+    # Detection starts where the stream is, so the first scene starts at frame 400
+    frame_numbers = detect_stream(video)
+
+    assert frame_numbers == [400, 533, 1121, 1778]
+
+def test_detect_stream_with_filters():
+    # Open the test video and seek to just before the last cut
+    video = open_video('test_files/bele_rade_1080.mp4')
+    video.seek(1700)
+
+    # Flash filtering and confirmation see the scores and the opening scene of the seeked part
+    frame_numbers = detect_stream(video, downscale=4, flash_window=3, confirm_full_resolution=True)
+
+    assert frame_numbers == [1700, 1778]
+
+def test_detect_multiple(tmpdir):
+    # One valid video and one missing file
+    test_video_path = 'test_files/bele_rade_1080.mp4'
+    missing_video_path = os.path.join(tmpdir, 'missing.mp4')
+
+    results = detect_multiple([test_video_path, missing_video_path], threshold=27.0)
+
+    # Verify each video gets its own result
+    frame_numbers, report = results[test_video_path]
+    assert frame_numbers == [0, 300, 533, 1121, 1778]
+    assert isinstance(results[missing_video_path], FileNotFoundError)
+
+def test_scene_labels_round_trip(tmpdir):
+    # Label and tag some of the scenes
+    database_path = os.path.join(tmpdir, 'test_output.db')
+    scenes = get_scene_list([0, 40, 75], 100, 25)
+    scenes[1].label = 'Interview'
+    scenes[1].tags = ['talking head', 'indoor', 'Smith, John']
+    scenes[2].notes = 'Check the color grade'
+
+    # Export the scenes and load them back
+    export_to_sqlite(database_path, 'video.mp4', scenes)
+    loaded_scenes = load_scenes_from_sqlite(database_path, 'video.mp4')
+
+    assert loaded_scenes == scenes
+
+def test_create_midi_with_labels(tmpdir):
+    # Scenes where only the second one is labeled, starting after the start of the video
+    output_filename = os.path.join(tmpdir, 'test_output.mid')
+    create_midi_with_markers([25, 50], output_filename, fps=25, marker_texts=[None, 'Interview'])
+
+    # Labels replace the default marker text, and the first marker keeps its position
+    markers = [msg for msg in MidiFile(output_filename).tracks[0] if msg.type == 'marker']
+    assert [msg.text for msg in markers] == ['SC 1', 'Interview']
+    assert [msg.time for msg in markers] == [960, 960]
+
+def test_merge_scene_changes(tmpdir):
+    # Known cuts given as frame numbers and timecodes, one close to a detected cut
+    extra_cuts_path = os.path.join(tmpdir, 'extra_cuts.txt')
+    with open(extra_cuts_path, 'w') as extra_cuts_file:
+        extra_cuts_file.write('# Cuts from the edit decision list\n302\n00:00:50.000\n')
+
+    extra_frame_numbers = load_extra_cuts(extra_cuts_path, 25)
+    merged_frame_numbers = merge_scene_changes([0, 300, 533, 1121, 1778], extra_frame_numbers)
+
+    # The known cut replaces the nearby detected one, the other is added
+    assert extra_frame_numbers == [302, 1250]
+    assert merged_frame_numbers == [0, 302, 533, 1121, 1250, 1778]
+    assert parse_timecode('01:00:00.040', 25) == 90001
+
+def test_scene_detection_excluding_ranges():
+    # Path to the test video file
+    test_video_path = 'test_files/bele_rade_1080.mp4'
+
+    # Exclude the range around the cut at frame 533 (21.32 seconds)
+    frame_numbers = detect_scene_changes_excluding(test_video_path, [(20.0, 30.0)])
+
+    assert frame_numbers == [0, 300, 1121, 1778]
+
+    # Whole numbers are seconds too, not frame numbers
+    assert detect_scene_changes_excluding(test_video_path, [(20, 30)]) == [0, 300, 1121, 1778]
+
+def test_group_matched_frames():
+    # Sampled every 5 frames, a bumper is matched twice in a row and once more later
+    matched_frames = [100, 105, 110, 300]
+
+    # Consecutive samples join into one event
+    assert group_matched_frames(matched_frames, 5) == [(100, 111), (300, 301)]
+
+def test_scene_changes_from_scores_merge_transitions():
+    # A hard cut at frame 20 and a 30 frame dissolve from frame 50 to 79
+    scores = [1.0] * 120
+    scores[20] = 60.0
+    for frame_number in range(50, 80):
+        scores[frame_number] = 30.0
+
+    # Without merging the dissolve gives an entry and an exit cut, with merging only one in its middle
+    assert scene_changes_from_scores(scores) == [0, 20, 50, 65]
+    assert scene_changes_from_scores(scores, merge_transitions=True) == [0, 20, 64]
+
+def test_export_difference_heatmaps(tmpdir):
+    # Path to the test video file
+    test_video_path = 'test_files/bele_rade_1080.mp4'
+
+    # Export heatmaps for the frames around the cut at frame 300
+    heatmap_paths = export_difference_heatmaps(test_video_path, [300], tmpdir, frames_around=1)
+
+    # One heatmap per frame, each the size of the video
+    assert [os.path.basename(path) for path in heatmap_paths] == ['heatmap_000299.png', 'heatmap_000300.png', 'heatmap_000301.png']
+    assert cv2.imread(heatmap_paths[1]).shape == (1080, 1920, 3)
+
+def test_weight_presets():
+    # Presets are looked up by name, and explicit weights are passed through
+    assert tuple(resolve_weights('luma_only')) == (0.0, 0.0, 1.0, 0.0)
+    assert resolve_weights((1.0, 0.5, 1.0, 0.0)).delta_sat == 0.5
+
+    # Users can add their own presets
+    register_weight_preset('saturation_only', (0.0, 1.0, 0.0, 0.0))
+    assert resolve_weights('saturation_only').delta_sat == 1.0
+
+    with pytest.raises(ValueError):
+        resolve_weights('missing_preset')
+
+def test_scene_detection_parallel():
+    # Path to the test video file
+    test_video_path = 'test_files/bele_rade_1080.mp4'
+
+    # Splitting the video into segments finds the same cuts as a single pass
+    frame_numbers = detect_scene_changes_parallel(test_video_path, segments=3)
+
+    assert frame_numbers == [0, 300, 533, 1121, 1778]
+
+def test_detect_scene_changes_parallel_limits(monkeypatch):
+    # Path to the test video file
+    test_video_path = 'test_files/bele_rade_1080.mp4'
+
+    # Frame limits are rejected, each segment has its own end
+    with pytest.raises(ValueError):
+        detect_scene_changes_parallel(test_video_path, max_frames=400)
+
+    # Cuts past a frame count estimated too low are still found by the last segment
+    video_info = get_video_info(test_video_path)
+    monkeypatch.setattr(scene_detection, 'get_video_info', lambda video_path: dict(video_info, frame_count=1500))
+    frame_numbers = detect_scene_changes_parallel(test_video_path, segments=3)
+
+    assert frame_numbers == [0, 300, 533, 1121, 1778]
+
+def test_drop_alpha():
+    # An opaque BGRA frame and a half transparent one
+    frame = np.full((8, 8, 4), 200, dtype=np.uint8)
+    frame[:, :, 3] = 255
+    transparent_frame = frame.copy()
+    transparent_frame[:4, :, 3] = 0
+
+    # Alpha is dropped or premultiplied, BGR frames pass through
+    assert drop_alpha(frame).shape == (8, 8, 3)
+    assert drop_alpha(transparent_frame, premultiply=True)[:4].max() == 0
+    assert drop_alpha(frame[:, :, :3]).shape == (8, 8, 3)
+
+    # BGRA frames can be scored directly
+    assert calculate_content_score(frame, frame) == 0
+
+def test_format_file_name():
+    # Scene numbers are zero-padded and timecodes made safe for file names
+    file_name = format_file_name('$VIDEO_NAME-Scene-$SCENE_NUMBER-$TIMECODE.$EXT', 'videos/bele_rade.mp4',
+                                 scene_number=7, frame_number=1500, fps=25.0, extension='png')
+    assert file_name == 'bele_rade-Scene-007-00-01-00.000.png'
+
+    # Unavailable placeholders are left as they are
+    assert format_file_name('$VIDEO_NAME-$SCENE_NUMBER', 'bele_rade.mp4') == 'bele_rade-$SCENE_NUMBER'
+
+def test_resolve_preset():
+    # Presets bundle options, explicit options override them
+    assert resolve_preset('fast') == {'downscale': 8, 'confirm_full_resolution': True}
+    assert resolve_preset('accurate', downscale=2)['downscale'] == 2
+    assert resolve_preset('balanced', threshold=30.0) == {'threshold': 30.0}
+
+    # Unknown presets are rejected
+    with pytest.raises(ValueError):
+        resolve_preset('fastest')
+
+def test_detection_warns_about_resolution_change():
+    # A stream that switches to a lower resolution rendition at frame 100
+    video = open_video('test_files/bele_rade_1080.mp4')
+    read_frame = video.read
+    def read_switching_rendition(*args, **kwargs):
+        frame = read_frame(*args, **kwargs)
+        if isinstance(frame, np.ndarray) and video.frame_number > 100:
+            frame = cv2.resize(frame, (1280, 720))
+        return frame
+    video.read = read_switching_rendition
+
+    # Detection carries on at the first resolution, and the change is reported
+    report = DetectionReport()
+    detect_scene_changes(None, video=video, max_frames=200, report=report)
+    assert report.frames_processed == 200
+    assert [warning for warning in report.warnings if 'resolution' in warning] == [
+        "Video resolution changes to 1280x720 at frame 100, frames are scored at 1920x1080 and results around the change may be inaccurate."]
+
+def test_scene_detection_with_cut_offset():
+    # Path to the test video file
+    test_video_path = 'test_files/bele_rade_1080.mp4'
+
+    # Cuts are moved to the last frame of the previous scene, the opening scene stays
+    frame_numbers = detect_scene_changes(test_video_path, cut_offset=-1)
+    assert frame_numbers == [0, 299, 532, 1120, 1777]
+
+    # Offsets can be given in seconds, one frame and one second at 25 FPS here, and
+    # integers of any kind are frames
+    assert detect_scene_changes(test_video_path, cut_offset=-0.04) == [0, 299, 532, 1120, 1777]
+    assert detect_scene_changes(test_video_path, cut_offset=-1.0) == [0, 275, 508, 1096, 1753]
+    assert detect_scene_changes(test_video_path, cut_offset=np.int64(-1)) == [0, 299, 532, 1120, 1777]
+
+    # Cuts don't move before the opening scene or onto it, and cuts moved onto the
+    # same frame become one
+    assert detect_scene_changes(test_video_path, cut_offset=-400) == [0, 1, 133, 721, 1378]
+    assert detect_scene_changes(test_video_path, cut_offset=-600) == [0, 1, 521, 1178]
+
+    # Nor past the last frame detected on
+    assert detect_scene_changes(test_video_path, max_frames=400, cut_offset=200) == [0, 399]
+
+def test_detection_observer():
+    # An observer that records what it is told
+    class RecordingObserver(DetectionObserver):
+        def __init__(self):
+            self.scored_frames = 0
+            self.cuts = []
+            self.warnings = []
+
+        def on_frame_scored(self, frame_number, score):
+            self.scored_frames += 1
+
+        def on_cut(self, frame_number):
+            self.cuts.append(frame_number)
+
+        def on_warning(self, message):
+            self.warnings.append(message)
+
+    # Detect scenes in the first 400 frames of the test video
+    observer = RecordingObserver()
+    frame_numbers = detect_scene_changes('test_files/bele_rade_1080.mp4', max_frames=400, observer=observer)
+
+    # Every frame is scored, and the cuts and truncation warning are passed on, but
+    # not the opening scene
+    assert observer.scored_frames >= 399
+    assert frame_numbers == [0, 300]
+    assert observer.cuts == [300]
+    assert len(observer.warnings) == 1
+
+def test_smooth_scores():
+    # A moving average spreads a single noisy frame over its neighbors
+    assert smooth_scores([0.0, 0.0, 9.0, 0.0, 0.0], 3) == [0.0, 3.0, 3.0, 3.0, 0.0]
+
+    # Noise that crosses the threshold on single frames no longer triggers cuts once smoothed
+    scores = [0.0] * 100
+    for frame_number in (20, 50):
+        scores[frame_number] = 30.0
+    scores[80:84] = [40.0] * 4
+    assert scene_changes_from_scores(scores) == [0, 20, 50, 80]
+    assert scene_changes_from_scores(scores, smoothing_window=3) == [0, 81]
+
+def test_hysteresis_thresholds():
+    # A transition whose score bounces around the threshold, then a second cut
+    scores = [0.0] * 100
+    scores[20:26] = [30.0, 25.0, 29.0, 24.0, 31.0, 10.0]
+    scores[60] = 35.0
+
+    # A single threshold cuts on every bounce (with a minimum scene length of 1),
+    # dual thresholds only once per transition
+    assert scene_changes_from_scores(scores, min_scene_len=1) == [0, 20, 22, 24, 60]
+    assert scene_changes_from_scores(scores, min_scene_len=1, low_threshold=15.0) == [0, 20, 60]
+
+    # A low threshold above the threshold would cut on every frame
+    with pytest.raises(ValueError):
+        scene_changes_from_scores(scores, low_threshold=40.0)
+
+def test_scene_detection_with_cooldown():
+    # Path to the test video file
+    test_video_path = 'test_files/bele_rade_1080.mp4'
+
+    # The cut 233 frames after the one at frame 300 falls in the cooldown
+    frame_numbers = detect_scene_changes(test_video_path, cooldown_frames=250)
+
+    assert frame_numbers == [0, 300, 1121, 1778]
+
+def test_scene_histograms():
+    # A red and a blue frame
+    red_frame = np.zeros((16, 16, 3), dtype=np.uint8)
+    red_frame[:, :, 2] = 255
+    blue_frame = np.zeros((16, 16, 3), dtype=np.uint8)
+    blue_frame[:, :, 0] = 255
+
+    # Histograms are normalized, and differ between the colors
+    red_histogram = calculate_hsv_histogram(red_frame)
+    blue_histogram = calculate_hsv_histogram(blue_frame)
+    assert red_histogram.shape == (128,) and red_histogram.sum() == pytest.approx(1.0)
+    assert red_histogram.argmax() != blue_histogram.argmax()
+
+    # Sums collected between detector cuts are averaged per final scene, where the
+    # cut at frame 2 was filtered out and frame 5 was cut by another detector
+    scene_histogram_sums = {0: (red_histogram * 2, 2), 2: (blue_histogram * 3, 3)}
+    scene_histograms = get_scene_histograms(scene_histogram_sums, [0, 5])
+    assert [start for start, _ in scene_histograms] == [0, 5]
+    assert scene_histograms[0][1] == pytest.approx((red_histogram * 2 + blue_histogram * 3) / 5)
+    assert scene_histograms[1][1].argmax() == blue_histogram.argmax()
+
+def test_scene_detection_with_histograms(tmpdir):
+    # Path to the test video file
+    test_video_path = 'test_files/bele_rade_1080.mp4'
+    histograms_file_path = os.path.join(tmpdir, 'histograms.json')
+
+    # One histogram is exported per scene
+    frame_numbers = detect_scene_changes(test_video_path, histograms_file_path=histograms_file_path)
+
+    with open(histograms_file_path) as histograms_file:
+        histograms = json.load(histograms_file)
+    assert [scene['start'] for scene in histograms['scenes']] == frame_numbers
+    assert len(histograms['scenes'][0]['histogram']) == 128
+
+def test_grab_scene_frame():
+    # Path to the test video file
+    test_video_path = 'test_files/bele_rade_1080.mp4'
+    scene = get_scene_list([0, 300, 533], 1121, 25)[1]
+
+    # The middle frame is returned as JPEG bytes, optionally as a smaller PNG thumbnail
+    jpeg_bytes = grab_scene_frame(test_video_path, scene)
+    png_bytes = grab_scene_frame(test_video_path, scene, position='start', image_format='png', max_width=320)
+
+    assert jpeg_bytes[:2] == b'\xff\xd8'
+    thumbnail = cv2.imdecode(np.frombuffer(png_bytes, dtype=np.uint8), cv2.IMREAD_COLOR)
+    assert thumbnail.shape == (180, 320, 3)
+
+def test_export_playlists(tmpdir):
+    # Two scenes of a 100 frame video at 25 FPS
+    scenes = get_scene_list([0, 40], 100, 25)
+    xspf_path = os.path.join(tmpdir, 'scenes.xspf')
+    edl_path = os.path.join(tmpdir, 'scenes.edl')
+
+    export_to_xspf(xspf_path, 'bele_rade.mp4', scenes, 25)
+    export_to_mpv_edl(edl_path, 'bele_rade.mp4', scenes, 25)
+
+    # Every scene is an entry that starts and stops at its boundaries
+    with open(xspf_path, encoding='utf-8') as xspf_file:
+        xspf = xspf_file.read()
+    assert xspf.count('<track>') == 2
+    assert 'start-time=1.600' in xspf and 'stop-time=4.000' in xspf
+
+    with open(edl_path, encoding='utf-8') as edl_file:
+        edl_lines = edl_file.read().splitlines()
+    assert edl_lines[0] == '# mpv EDL v0'
+    assert [line.split(',')[-2:] for line in edl_lines[1:]] == [['0.000', '1.600'], ['1.600', '2.400']]
+
+def test_get_capabilities():
+    capabilities = get_capabilities()
+
+    # The OpenCV backend is always there, and the report can be saved as JSON
+    assert 'opencv' in capabilities['video_backends']
+    assert 'FFMPEG' in capabilities['opencv_video_io']
+    assert json.loads(json.dumps(capabilities)) == capabilities
+
+def test_scene_detection_with_frame_callback():
+    # Collect the mean brightness and metrics of every frame during detection
+    brightness = {}
+    def frame_callback(frame_number, frame, score, metrics):
+        assert not frame.flags.writeable
+        assert frame.shape[:2] == (1080, 1920)
+        brightness[frame_number] = (frame.mean(), score, metrics)
+
+    frame_numbers = detect_scene_changes('test_files/bele_rade_1080.mp4', max_frames=400, frame_callback=frame_callback)
+
+    # Every frame was seen once at full resolution, with the score and per-channel
+    # deltas that produced the cut
+    assert frame_numbers == [0, 300]
+    assert sorted(brightness) == list(range(400))
+    _, score, metrics = brightness[300]
+    assert score >= 27.0
+    assert metrics.frame_number == 300 and metrics.content_val == score
+    assert max(metrics.delta_hue, metrics.delta_sat, metrics.delta_lum) > 0
+
+def test_check_cut_rate():
+    # Normal cutting, a short burst of fast cuts and a long video with a few cuts are fine
+    assert check_cut_rate([0, 300, 533, 1121, 1778], 2000, 25) is None
+    assert check_cut_rate([0] + list(range(100, 500, 10)), 2000, 25) is None
+    assert check_cut_rate([0, 50000], 100000, 25) is None
+
+    # Sustained cuts every half second, or none at all in over an hour, are flagged
+    assert 'too low' in check_cut_rate([0] + list(range(12, 3000, 12)), 3000, 25)
+    assert 'too high' in check_cut_rate([0], 100000, 25)
+
+def test_cut_probabilities():
+    # Scores at the threshold are even odds, far above or below it nearly certain
+    probabilities = cut_probabilities([0.0, 27.0, 30.0, 80.0, 1e9], threshold=27.0)
+
+    assert probabilities[1] == pytest.approx(0.5)
+    assert probabilities[0] < 0.01 and probabilities[3] > 0.99 and probabilities[4] == pytest.approx(1.0)
+    assert 0.5 < probabilities[2] < probabilities[3]
+
+    # A zero threshold needs an explicit softness
+    with pytest.raises(ValueError):
+        cut_probabilities([0.0, 5.0], threshold=0.0)
+    assert cut_probabilities([0.0], threshold=0.0, softness=1.0) == pytest.approx([0.5])
+
+def test_threshold_for_scene_count():
+    # Cuts of decreasing strength every 20 frames
+    scores = [0.0] * 200
+    for i, frame_number in enumerate(range(20, 200, 20)):
+        scores[frame_number] = 60.0 - i * 5
+
+    # The threshold is lowered or raised until the scene count is close enough
+    threshold, frame_numbers = threshold_for_scene_count(scores, 5, tolerance=0)
+    assert frame_numbers == [0, 20, 40, 60, 80]
+    assert 40.0 < threshold <= 45.0
+
+    # With no way to reach the target, the closest result is returned
+    assert threshold_for_scene_count(scores, 50)[1] == [0] + list(range(20, 200, 20))
+
+def test_scene_detection_with_pixel_stride():
+    # Path to the test video file
+    test_video_path = 'test_files/bele_rade_1080.mp4'
+
+    # Scoring every other row and column of the frames finds the same cuts
+    frame_numbers = detect_scene_changes(test_video_path, pixel_stride=2)
+
+    assert frame_numbers == [0, 300, 533, 1121, 1778]
+
+def test_custom_metric_blending():
+    # Two different frames, scored by detectors with and without extra metrics
+    rng = np.random.default_rng(0)
+    previous_frame = rng.integers(0, 255, (32, 32, 3), dtype=np.uint8)
+    current_frame = rng.integers(0, 255, (32, 32, 3), dtype=np.uint8)
+    def score_pair(detector):
+        detector._calculate_frame_score(0, previous_frame)
+        return detector._calculate_frame_score(1, current_frame)
+
+    content_score = score_pair(MarkItContentDetector())
+    weighted_score = score_pair(MarkItContentDetector(custom_metrics=[(lambda previous, current: 100.0, 1.0)]))
+    unweighted_score = score_pair(MarkItContentDetector(custom_metrics=[(lambda previous, current: 100.0, 0.0)]))
+
+    # A weighted metric is averaged in with the content score, a zero weight changes nothing
+    assert weighted_score == pytest.approx((content_score + 100.0) / 2)
+    assert weighted_score != pytest.approx(content_score)
+    assert unweighted_score == pytest.approx(content_score)
+
+def test_detect_with_timeout():
+    # Path to the test video file
+    test_video_path = 'test_files/bele_rade_1080.mp4'
+
+    # Stop detection after half a second, long before the whole video is decoded
+    frame_numbers, report = detect_with_report(test_video_path, timeout=0.5)
+
+    # Partial results are returned and flagged as truncated
+    assert report.truncated and 0 < report.frames_processed < report.total_frames
+    assert frame_numbers[0] == 0 and all(frame_number < report.frames_processed for frame_number in frame_numbers)
+    assert any('timed out' in warning for warning in report.warnings)